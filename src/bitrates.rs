@@ -3,6 +3,52 @@
 // cnf3.wakfil = false
 // cnf2.sam = ?
 
+use crate::registers::CNF;
+
+/// Bit timing resulting from a set of CNF registers
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TimingInfo {
+    /// Bits per second, rounded down
+    pub bitrate: u32,
+    /// Position of the sample point within the bit in 1/1000 of the bit time
+    pub sample_point_permille: u16,
+    /// Number of time quanta per bit
+    pub time_quanta: u8,
+    /// Synchronization Jump Width in time quanta
+    pub sjw: u8,
+}
+
+impl TimingInfo {
+    /// Compute the timing of `cnf` for an oscillator running at `osc_hz`
+    ///
+    /// ```
+    /// use mcp25xx::bitrates::{clock_16mhz::CNF_500K_BPS, TimingInfo};
+    ///
+    /// let timing = TimingInfo::from_cnf(CNF_500K_BPS, 16_000_000);
+    /// assert_eq!(timing.bitrate, 500_000);
+    /// assert_eq!(timing.time_quanta, 16);
+    /// assert_eq!(timing.sample_point_permille, 562);
+    /// ```
+    pub fn from_cnf(cnf: CNF, osc_hz: u32) -> Self {
+        let prop_seg = cnf.cnf2.prseg() as u32 + 1;
+        let phase_seg1 = cnf.cnf2.phseg1() as u32 + 1;
+        let phase_seg2 = if cnf.cnf2.btlmode() {
+            cnf.cnf3.phseg2() as u32 + 1
+        } else {
+            // the information processing time is 2 time quanta
+            core::cmp::max(phase_seg1, 2)
+        };
+        let time_quanta = 1 + prop_seg + phase_seg1 + phase_seg2;
+        let tq_per_second = osc_hz / (2 * (cnf.cnf1.brp() as u32 + 1));
+        TimingInfo {
+            bitrate: tq_per_second / time_quanta,
+            sample_point_permille: ((1 + prop_seg + phase_seg1) * 1000 / time_quanta) as u16,
+            time_quanta: time_quanta as u8,
+            sjw: cnf.cnf1.sjw() + 1,
+        }
+    }
+}
+
 /// Preconfigured CNF registers for 8 Mhz oscillators
 pub mod clock_8mhz {
     use crate::registers::CNF;
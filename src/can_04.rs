@@ -0,0 +1,102 @@
+use core::fmt::Debug;
+
+use embedded_can_04 as can;
+
+use crate::{CanFrame, Error, MCP25xx, SpiWithCs};
+
+fn from_id(id: can::Id) -> embedded_can::Id {
+    // SAFETY:
+    // both versions enforce the same ranges for their ids
+    unsafe {
+        match id {
+            can::Id::Standard(id) => embedded_can::StandardId::new_unchecked(id.as_raw()).into(),
+            can::Id::Extended(id) => embedded_can::ExtendedId::new_unchecked(id.as_raw()).into(),
+        }
+    }
+}
+
+fn to_id(id: embedded_can::Id) -> can::Id {
+    // SAFETY:
+    // both versions enforce the same ranges for their ids
+    unsafe {
+        match id {
+            embedded_can::Id::Standard(id) => can::StandardId::new_unchecked(id.as_raw()).into(),
+            embedded_can::Id::Extended(id) => can::ExtendedId::new_unchecked(id.as_raw()).into(),
+        }
+    }
+}
+
+impl can::Frame for CanFrame {
+    fn new(id: impl Into<can::Id>, data: &[u8]) -> Option<Self> {
+        embedded_can::Frame::new(from_id(id.into()), data).ok()
+    }
+
+    fn new_remote(id: impl Into<can::Id>, dlc: usize) -> Option<Self> {
+        embedded_can::Frame::new_remote(from_id(id.into()), dlc).ok()
+    }
+
+    #[inline]
+    fn is_extended(&self) -> bool {
+        embedded_can::Frame::is_extended(self)
+    }
+
+    #[inline]
+    fn is_remote_frame(&self) -> bool {
+        embedded_can::Frame::is_remote_frame(self)
+    }
+
+    #[inline]
+    fn id(&self) -> can::Id {
+        to_id(embedded_can::Frame::id(self))
+    }
+
+    #[inline]
+    fn dlc(&self) -> usize {
+        embedded_can::Frame::dlc(self)
+    }
+
+    #[inline]
+    fn data(&self) -> &[u8] {
+        embedded_can::Frame::data(self)
+    }
+}
+
+impl<E: Debug> can::Error for Error<E> {
+    fn kind(&self) -> can::ErrorKind {
+        can::ErrorKind::Other
+    }
+}
+
+impl<SPI, CS> can::nb::Can for MCP25xx<SPI, CS>
+where
+    Self: SpiWithCs,
+{
+    type Frame = CanFrame;
+    type Error = Error<<Self as SpiWithCs>::Error>;
+
+    fn transmit(&mut self, frame: &CanFrame) -> nb::Result<Option<CanFrame>, Self::Error> {
+        embedded_can::Can::try_transmit(self, frame).map_err(|e| e.map(Error::Spi))
+    }
+
+    fn receive(&mut self) -> nb::Result<CanFrame, Self::Error> {
+        self.try_receive_checked()
+    }
+}
+
+impl<SPI, CS> can::blocking::Can for MCP25xx<SPI, CS>
+where
+    Self: SpiWithCs,
+{
+    type Frame = CanFrame;
+    type Error = Error<<Self as SpiWithCs>::Error>;
+
+    fn transmit(&mut self, frame: &CanFrame) -> Result<(), Self::Error> {
+        // never replaces a pending frame, one returned here would be dropped
+        nb::block!(can::nb::Can::transmit(self, frame))?;
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<CanFrame, Self::Error> {
+        nb::block!(can::nb::Can::receive(self))
+    }
+}
@@ -1,5 +1,9 @@
-use crate::registers::{OperationMode, CANCTRL, CNF, RXB0CTRL, RXB1CTRL};
-use crate::{AcceptanceFilter, IdHeader};
+use core::array::TryFromSliceError;
+
+#[cfg(not(feature = "fast_instructions"))]
+use crate::registers::RXM;
+use crate::registers::{OperationMode, PhSeg2Source, CANCTRL, CNF, RXB0CTRL, RXB1CTRL};
+use crate::{AcceptanceFilter, Error, IdHeader, MCP25xx, SpiWithCs};
 
 /// Configuration for:
 /// * Clock settings
@@ -14,6 +18,13 @@ pub struct Config<'a> {
     pub rxb0ctrl: RXB0CTRL,
     pub rxb1ctrl: RXB1CTRL,
     pub filters: &'a [(AcceptanceFilter, IdHeader)],
+    /// SAM bit chosen with [`Config::triple_sampling`], kept by [`Config::bitrate`]
+    sam_override: Option<bool>,
+    /// BTLMODE bit chosen with [`Config::btl_mode`], kept by [`Config::bitrate`]
+    btl_mode_override: Option<PhSeg2Source>,
+    /// SOF bit chosen with [`Config::start_of_frame_output`], kept by [`Config::bitrate`]
+    #[cfg(feature = "fast_instructions")]
+    sof_override: Option<bool>,
 }
 
 impl<'a> Config<'a> {
@@ -27,9 +38,69 @@ impl<'a> Config<'a> {
         self.canctrl = canctrl;
         self
     }
+    /// Clock settings, see [`crate::bitrates`]
+    ///
+    /// The settings made by [`Config::wake_filter`], [`Config::triple_sampling`],
+    /// [`Config::btl_mode`] and `Config::start_of_frame_output` are kept.
     #[inline]
     pub fn bitrate(mut self, cnf: CNF) -> Self {
+        let cnf3 = self.cnf.cnf3;
         self.cnf = cnf;
+        if let Some(enabled) = self.sam_override {
+            self.cnf.cnf2.set_sam(enabled);
+        }
+        if let Some(source) = self.btl_mode_override {
+            self.cnf = self.cnf.with_btl_mode(source);
+        }
+        self.cnf.cnf3.set_wakfil(cnf3.wakfil());
+        #[cfg(feature = "fast_instructions")]
+        if let Some(enabled) = self.sof_override {
+            self.cnf.cnf3.set_sof(enabled);
+        }
+        self
+    }
+    /// Enable the low-pass filter on the wake-up signal (WAKFIL bit of CNF3)
+    ///
+    /// While in Sleep mode, bus activity wakes the controller up if the `wakie` interrupt
+    /// is enabled. The filter rejects short glitches on the bus so noise does not wake the node.
+    /// It has no influence on reception outside of Sleep mode.
+    #[inline]
+    pub fn wake_filter(mut self, enabled: bool) -> Self {
+        self.cnf.cnf3.set_wakfil(enabled);
+        self
+    }
+    /// Sample the bus three times per bit and take the majority (SAM bit of CNF2)
+    ///
+    /// Improves noise immunity at low bitrates. The extra samples are taken before the sample point,
+    /// which leaves too little time at high bitrates, so keep it disabled above about 125 kbps.
+    #[inline]
+    pub fn triple_sampling(mut self, enabled: bool) -> Self {
+        self.cnf.cnf2.set_sam(enabled);
+        self.sam_override = Some(enabled);
+        self
+    }
+    /// Select where the length of Phase Segment 2 comes from (BTLMODE bit of CNF2)
+    ///
+    /// All tables in [`crate::bitrates`] use [`PhSeg2Source::Cnf3`].
+    #[inline]
+    pub fn btl_mode(mut self, source: PhSeg2Source) -> Self {
+        self.cnf = self.cnf.with_btl_mode(source);
+        self.btl_mode_override = Some(source);
+        self
+    }
+    /// Output a start-of-frame signal on the CLKOUT/SOF pin (SOF bit of CNF3)
+    ///
+    /// The pin only works while enabled by the CLKEN bit of CANCTRL, so enabling also sets CLKEN.
+    /// Disabling returns the pin to the clock output.
+    #[cfg(feature = "fast_instructions")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+    #[inline]
+    pub fn start_of_frame_output(mut self, enabled: bool) -> Self {
+        self.cnf.cnf3.set_sof(enabled);
+        self.sof_override = Some(enabled);
+        if enabled {
+            self.canctrl.set_clken(true);
+        }
         self
     }
     #[inline]
@@ -42,9 +113,195 @@ impl<'a> Config<'a> {
         self.rxb1ctrl = rxb1ctrl;
         self
     }
+    /// Accept only frames with standard ids in both receive buffers
+    ///
+    /// Sets [`RXM::FilterStandard`] and keeps the other bits of RXB0CTRL and RXB1CTRL.
+    /// Filters and masks still apply, with the default masks all standard frames are received.
+    /// The MCP2515 and MCP25625 do not support this mode.
+    #[cfg(not(feature = "fast_instructions"))]
+    #[inline]
+    pub fn receive_standard_only(mut self) -> Self {
+        self.rxb0ctrl.set_rxm(RXM::FilterStandard);
+        self.rxb1ctrl.set_rxm(RXM::FilterStandard);
+        self
+    }
+    /// Accept only frames with extended ids in both receive buffers
+    ///
+    /// Sets [`RXM::FilterExtended`], see [`Config::receive_standard_only`].
+    #[cfg(not(feature = "fast_instructions"))]
+    #[inline]
+    pub fn receive_extended_only(mut self) -> Self {
+        self.rxb0ctrl.set_rxm(RXM::FilterExtended);
+        self.rxb1ctrl.set_rxm(RXM::FilterExtended);
+        self
+    }
     #[inline]
     pub fn filters(mut self, filters: &'a [(AcceptanceFilter, IdHeader)]) -> Self {
         self.filters = filters;
         self
     }
+    /// Whether the clock settings differ from the all zero reset value
+    ///
+    /// `Config::default()` keeps the reset value until [`Config::bitrate`] is called, a node
+    /// configured with it does not communicate with anything, see [`CNF::is_reset_default`].
+    /// [`MCP25xx::init`](crate::MCP25xx::init) rejects such configurations.
+    #[inline]
+    pub fn has_bitrate(&self) -> bool {
+        !self.cnf.is_reset_default()
+    }
+}
+
+/// [`Config`] holding its filters inline, for configurations built as a `const`
+///
+/// ```
+/// # use mcp25xx::doctesthelper::get_mcp25xx;
+/// use embedded_can::{Id, StandardId};
+/// use mcp25xx::bitrates::clock_16mhz::CNF_500K_BPS;
+/// use mcp25xx::registers::{CANCTRL, RXB0CTRL, RXB1CTRL, RXM};
+/// use mcp25xx::{AcceptanceFilter, Config, IdHeader, MCP25xx, StaticConfig};
+///
+/// const CONFIG: StaticConfig<2> = StaticConfig {
+///     // Normal mode, clock output enabled
+///     canctrl: CANCTRL::from_bytes([0b0000_0111]),
+///     cnf: CNF_500K_BPS,
+///     rxb0ctrl: RXB0CTRL::new(),
+///     rxb1ctrl: RXB1CTRL::new(),
+///     filters: [
+///         (AcceptanceFilter::Mask0, IdHeader::from_bytes([0xFF, 0xE0, 0, 0])),
+///         // accept id 0x123
+///         (AcceptanceFilter::Filter0, IdHeader::from_bytes([0x24, 0x60, 0, 0])),
+///     ],
+/// };
+/// assert_eq!(CONFIG.filters[1].1.id(), Id::Standard(StandardId::new(0x123).unwrap()));
+///
+/// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+/// mcp25xx.apply_config(&Config::from(&CONFIG)).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct StaticConfig<const F: usize> {
+    pub canctrl: CANCTRL,
+    pub cnf: CNF,
+    pub rxb0ctrl: RXB0CTRL,
+    pub rxb1ctrl: RXB1CTRL,
+    pub filters: [(AcceptanceFilter, IdHeader); F],
+}
+
+impl<const F: usize> StaticConfig<F> {
+    /// Default register values with the given filters
+    pub const fn new(filters: [(AcceptanceFilter, IdHeader); F]) -> Self {
+        StaticConfig {
+            canctrl: CANCTRL::from_bytes([0b1000_0111]),
+            cnf: CNF::from_bytes([0; 3]),
+            rxb0ctrl: RXB0CTRL::new(),
+            rxb1ctrl: RXB1CTRL::new(),
+            filters,
+        }
+    }
+}
+
+impl<'a, const F: usize> From<&'a StaticConfig<F>> for Config<'a> {
+    fn from(config: &'a StaticConfig<F>) -> Self {
+        Config {
+            canctrl: config.canctrl,
+            cnf: config.cnf,
+            rxb0ctrl: config.rxb0ctrl,
+            rxb1ctrl: config.rxb1ctrl,
+            filters: &config.filters,
+            sam_override: None,
+            btl_mode_override: None,
+            #[cfg(feature = "fast_instructions")]
+            sof_override: None,
+        }
+    }
+}
+
+/// Fails if the config does not have exactly `F` filters
+impl<const F: usize> TryFrom<&Config<'_>> for StaticConfig<F> {
+    type Error = TryFromSliceError;
+
+    fn try_from(config: &Config<'_>) -> Result<Self, Self::Error> {
+        Ok(StaticConfig {
+            canctrl: config.canctrl,
+            cnf: config.cnf,
+            rxb0ctrl: config.rxb0ctrl,
+            rxb1ctrl: config.rxb1ctrl,
+            filters: config.filters.try_into()?,
+        })
+    }
+}
+
+/// Several configurations to switch between, e.g. for a tool probing different bitrates
+///
+/// Remembers the configuration applied last through [`ConfigSet::switch_config`]
+/// and skips applying it again.
+///
+/// ```
+/// # use mcp25xx::doctesthelper::get_mcp25xx;
+/// use mcp25xx::bitrates::clock_16mhz::{CNF_250K_BPS, CNF_500K_BPS};
+/// use mcp25xx::{ConfigSet, MCP25xx, StaticConfig};
+///
+/// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+///
+/// let mut configs = ConfigSet::new([
+///     StaticConfig { cnf: CNF_250K_BPS, ..StaticConfig::new([]) },
+///     StaticConfig { cnf: CNF_500K_BPS, ..StaticConfig::new([]) },
+/// ]);
+/// configs.switch_config(&mut mcp25xx, 1).unwrap();
+/// assert_eq!(configs.current(), Some(1));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConfigSet<const N: usize, const F: usize> {
+    configs: [StaticConfig<F>; N],
+    current: Option<usize>,
+}
+
+impl<const N: usize, const F: usize> ConfigSet<N, F> {
+    pub const fn new(configs: [StaticConfig<F>; N]) -> Self {
+        ConfigSet {
+            configs,
+            current: None,
+        }
+    }
+
+    /// Index of the configuration applied last, `None` before the first switch
+    #[inline]
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Apply the configuration at `index` with [`MCP25xx::apply_config`],
+    /// unless it was the last one applied
+    ///
+    /// Fails with [`Error::InvalidArgument`] if there is no configuration at `index`
+    /// or its clock settings are still at the reset value, see [`Config::has_bitrate`].
+    /// Configuring the controller by other means is not noticed, call
+    /// [`ConfigSet::invalidate`] afterwards to apply the next configuration in any case.
+    pub fn switch_config<SPI, CS>(
+        &mut self,
+        mcp25xx: &mut MCP25xx<SPI, CS>,
+        index: usize,
+    ) -> Result<(), Error<<MCP25xx<SPI, CS> as SpiWithCs>::Error>>
+    where
+        MCP25xx<SPI, CS>: SpiWithCs,
+    {
+        let config = self.configs.get(index).ok_or(Error::InvalidArgument)?;
+        if config.cnf.is_reset_default() {
+            return Err(Error::InvalidArgument);
+        }
+        if self.current == Some(index) {
+            return Ok(());
+        }
+        // a failed attempt leaves the controller in an unknown state
+        self.current = None;
+        mcp25xx
+            .apply_config(&Config::from(config))
+            .map_err(Error::Spi)?;
+        self.current = Some(index);
+        Ok(())
+    }
+
+    /// Forget which configuration was applied last
+    pub fn invalidate(&mut self) {
+        self.current = None;
+    }
 }
@@ -0,0 +1,86 @@
+use crate::registers::{OperationMode, CNF, TXRTSCTRL};
+use crate::{AcceptanceFilter, IdHeader, MCP25xx, SpiWithCs};
+
+/// Guard keeping the controller in Configuration mode
+///
+/// Created by [`MCP25xx::enter_config_mode`].
+/// Restores the previous operation mode when dropped.
+pub struct ConfigMode<'a, SPI, CS>
+where
+    MCP25xx<SPI, CS>: SpiWithCs,
+{
+    pub(crate) mcp25xx: &'a mut MCP25xx<SPI, CS>,
+    pub(crate) previous_mode: OperationMode,
+}
+
+impl<SPI, CS> ConfigMode<'_, SPI, CS>
+where
+    MCP25xx<SPI, CS>: SpiWithCs,
+{
+    /// Set clock settings
+    ///
+    /// See [`crate::bitrates`] for preconfigured settings for different oscillator frequencies.
+    #[inline]
+    pub fn set_bitrate(&mut self, cnf: CNF) -> Result<(), <MCP25xx<SPI, CS> as SpiWithCs>::Error> {
+        self.mcp25xx.set_bitrate(cnf)
+    }
+
+    /// Set individual receive buffer filters or masks
+    #[inline]
+    pub fn set_filter(
+        &mut self,
+        filter: AcceptanceFilter,
+        id: IdHeader,
+    ) -> Result<(), <MCP25xx<SPI, CS> as SpiWithCs>::Error> {
+        self.mcp25xx.set_filter(filter, id)
+    }
+
+    /// Read back a receive buffer filter or mask
+    #[inline]
+    pub fn read_filter(
+        &mut self,
+        filter: AcceptanceFilter,
+    ) -> Result<IdHeader, <MCP25xx<SPI, CS> as SpiWithCs>::Error> {
+        self.mcp25xx.read_filter(filter)
+    }
+
+    /// Set the masks of both receive buffers
+    pub fn set_masks(
+        &mut self,
+        mask0: IdHeader,
+        mask1: IdHeader,
+    ) -> Result<(), <MCP25xx<SPI, CS> as SpiWithCs>::Error> {
+        self.mcp25xx.set_filter(AcceptanceFilter::Mask0, mask0)?;
+        self.mcp25xx.set_filter(AcceptanceFilter::Mask1, mask1)
+    }
+
+    /// Select the function of the TX0RTS, TX1RTS and TX2RTS pins
+    ///
+    /// A `true` entry makes a falling edge on the pin request the transmission of its buffer,
+    /// `false` turns the pin into a digital input read by [`MCP25xx::read_tx_rts_inputs`].
+    pub fn set_tx_rts_pins(
+        &mut self,
+        request_to_send: [bool; 3],
+    ) -> Result<(), <MCP25xx<SPI, CS> as SpiWithCs>::Error> {
+        let txrtsctrl = TXRTSCTRL::new()
+            .with_b0rtsm(request_to_send[0])
+            .with_b1rtsm(request_to_send[1])
+            .with_b2rtsm(request_to_send[2]);
+        self.mcp25xx.modify_register(txrtsctrl, 0b0000_0111)
+    }
+
+    /// Operation mode the controller returns to when the guard is dropped
+    #[inline]
+    pub fn previous_mode(&self) -> OperationMode {
+        self.previous_mode
+    }
+}
+
+impl<SPI, CS> Drop for ConfigMode<'_, SPI, CS>
+where
+    MCP25xx<SPI, CS>: SpiWithCs,
+{
+    fn drop(&mut self) {
+        self.mcp25xx.set_mode(self.previous_mode).ok();
+    }
+}
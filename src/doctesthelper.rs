@@ -1,20 +1,35 @@
 use core::convert::Infallible;
 
+use embedded_hal::blocking::delay::DelayUs;
 use embedded_hal::blocking::spi::{Transfer, Write};
 use embedded_hal::digital::v2::OutputPin;
 
-use crate::MCP25xx;
+use crate::registers::{Register, CANCTRL, CANSTAT};
+use crate::{Instruction, MCP25xx};
 
 /// used for doc tests
 pub fn get_mcp25xx() -> MCP25xx<NoOpSPI, NoOpCS> {
-    MCP25xx {
-        spi: NoOpSPI,
-        cs: NoOpCS,
-    }
+    MCP25xx::new(NoOpSPI::default(), NoOpCS)
 }
 
 pub struct NoOpCS;
-pub struct NoOpSPI;
+
+/// Reads back zeros, except for CANSTAT which follows the requested operation mode
+pub struct NoOpSPI {
+    canctrl: u8,
+    read_address: Option<u8>,
+    write_address: Option<u8>,
+}
+
+impl Default for NoOpSPI {
+    fn default() -> Self {
+        NoOpSPI {
+            canctrl: CANCTRL::default().into(),
+            read_address: None,
+            write_address: None,
+        }
+    }
+}
 
 impl OutputPin for NoOpCS {
     type Error = Infallible;
@@ -31,7 +46,28 @@ impl OutputPin for NoOpCS {
 impl Write<u8> for NoOpSPI {
     type Error = Infallible;
 
-    fn write(&mut self, _: &[u8]) -> Result<(), Self::Error> {
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        const RESET: u8 = Instruction::Reset as u8;
+        const READ: u8 = Instruction::Read as u8;
+        const WRITE: u8 = Instruction::Write as u8;
+        const BIT_MODIFY: u8 = Instruction::BitModify as u8;
+
+        if let Some(address) = self.write_address.take() {
+            if let Some(&canctrl) = words.get(CANCTRL::ADDRESS.wrapping_sub(address) as usize) {
+                self.canctrl = canctrl;
+            }
+            return Ok(());
+        }
+        match *words {
+            [RESET] => self.canctrl = CANCTRL::default().into(),
+            [READ, address] => self.read_address = Some(address),
+            [WRITE, address] => self.write_address = Some(address),
+            [WRITE, CANCTRL::ADDRESS, canctrl, ..] => self.canctrl = canctrl,
+            [BIT_MODIFY, CANCTRL::ADDRESS, mask, data] => {
+                self.canctrl = (self.canctrl & !mask) | (data & mask)
+            }
+            _ => {}
+        }
         Ok(())
     }
 }
@@ -40,6 +76,36 @@ impl Transfer<u8> for NoOpSPI {
     type Error = Infallible;
 
     fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        if let Some(address) = self.read_address.take() {
+            let offset = CANSTAT::ADDRESS.wrapping_sub(address) as usize;
+            if let Some(canstat) = words.get_mut(offset) {
+                *canstat = self.canctrl & 0b1110_0000;
+            }
+        }
         Ok(words)
     }
 }
+
+pub struct NoOpSPI16;
+
+impl Write<u16> for NoOpSPI16 {
+    type Error = Infallible;
+
+    fn write(&mut self, _: &[u16]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl Transfer<u16> for NoOpSPI16 {
+    type Error = Infallible;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u16]) -> Result<&'w [u16], Self::Error> {
+        Ok(words)
+    }
+}
+
+pub struct NoOpDelay;
+
+impl DelayUs<u32> for NoOpDelay {
+    fn delay_us(&mut self, _: u32) {}
+}
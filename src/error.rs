@@ -0,0 +1,20 @@
+/// Errors returned by driver functions that can fail for reasons other than the SPI bus
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    /// Error of the underlying SPI bus
+    Spi(E),
+    /// The frame could not be transmitted within the allowed number of attempts
+    RetriesExhausted,
+    /// The controller did not confirm the requested operation mode
+    ModeChangeTimeout,
+    /// An argument was outside of its valid range
+    InvalidArgument,
+    /// A transmit buffer needed for the operation has a pending transmit request
+    Busy,
+    /// The transmission ended without success, see the `txerr`, `mloa` and `abtf` bits of [`TXB0CTRL`](crate::registers::TXB0CTRL)
+    TransmitFailed,
+    /// An expected frame was not received in time
+    Timeout,
+    /// A frame with a DLC above 8 was received in strict DLC mode, see [`MCP25xx::with_strict_dlc`](crate::MCP25xx::with_strict_dlc)
+    MalformedFrame,
+}
@@ -0,0 +1,150 @@
+use crate::registers::RXM;
+use crate::{AcceptanceFilter, Config, IdHeader, RxBuffer};
+
+/// All filters and masks in the order the controller evaluates them
+const SLOTS: [AcceptanceFilter; 8] = [
+    AcceptanceFilter::Mask0,
+    AcceptanceFilter::Filter0,
+    AcceptanceFilter::Filter1,
+    AcceptanceFilter::Mask1,
+    AcceptanceFilter::Filter2,
+    AcceptanceFilter::Filter3,
+    AcceptanceFilter::Filter4,
+    AcceptanceFilter::Filter5,
+];
+
+/// Problem with the filters of a [`Config`] found by [`FilterPlan`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FilterIssue {
+    /// The filter or mask is listed more than once, only the last entry takes effect
+    Overwritten(AcceptanceFilter),
+    /// The filter is set, but its receive buffer accepts any frame with [`RXM::ReceiveAny`]
+    Unreachable(AcceptanceFilter),
+    /// The filter accepts the same frames as an earlier filter of the same receive buffer
+    Redundant(AcceptanceFilter),
+    /// Filters of the receive buffer are set, but the mask is not.
+    /// The mask is zero after a reset, so the buffer accepts all frames.
+    MissingMask(AcceptanceFilter),
+    /// The mask of the receive buffer is set, but this filter is not.
+    /// Filters are undefined after power-up and may accept arbitrary ids.
+    UnsetFilter(AcceptanceFilter),
+}
+
+/// Checks the filters and masks of a [`Config`] against the fixed assignment to the receive buffers
+///
+/// See [`AcceptanceFilter::receive_buffer`] for the filters and mask of each buffer.
+///
+/// ```
+/// use embedded_can::StandardId;
+/// use mcp25xx::registers::{RXB1CTRL, RXM};
+/// use mcp25xx::{AcceptanceFilter, Config, FilterIssue, FilterPlan, IdHeader};
+///
+/// let id: IdHeader = StandardId::new(0x100).unwrap().into();
+/// let filters = [
+///     (AcceptanceFilter::Mask0, StandardId::MAX.into()),
+///     (AcceptanceFilter::Filter0, id),
+///     (AcceptanceFilter::Filter1, id),
+///     // meant for RXB0, but Filter2 belongs to RXB1
+///     (AcceptanceFilter::Filter2, id),
+/// ];
+/// let config = Config::default()
+///     .receive_buffer_1(RXB1CTRL::default().with_rxm(RXM::ReceiveAny))
+///     .filters(&filters);
+///
+/// let plan = FilterPlan::new(&config);
+/// let mut issues = plan.issues();
+/// assert_eq!(issues.next(), Some(FilterIssue::Redundant(AcceptanceFilter::Filter1)));
+/// assert_eq!(issues.next(), Some(FilterIssue::Unreachable(AcceptanceFilter::Filter2)));
+/// assert_eq!(issues.next(), None);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct FilterPlan<'a> {
+    filters: &'a [(AcceptanceFilter, IdHeader)],
+    receive_any: [bool; 2],
+}
+
+impl<'a> FilterPlan<'a> {
+    /// Plan for the filters and receive buffer modes of `config`
+    pub fn new(config: &Config<'a>) -> Self {
+        FilterPlan {
+            filters: config.filters,
+            receive_any: [
+                matches!(config.rxb0ctrl.rxm(), RXM::ReceiveAny),
+                matches!(config.rxb1ctrl.rxm(), RXM::ReceiveAny),
+            ],
+        }
+    }
+
+    /// All issues found, at most one per filter or mask
+    pub fn issues(&self) -> impl Iterator<Item = FilterIssue> + '_ {
+        SLOTS.into_iter().filter_map(move |slot| self.issue(slot))
+    }
+
+    /// First issue found, if any
+    pub fn check(&self) -> Result<(), FilterIssue> {
+        self.issues().next().map_or(Ok(()), Err)
+    }
+
+    /// Value the slot ends up with
+    fn value(&self, slot: AcceptanceFilter) -> Option<IdHeader> {
+        self.filters
+            .iter()
+            .rev()
+            .find(|(filter, _)| *filter == slot)
+            .map(|&(_, header)| header)
+    }
+
+    fn mask_of(buffer: RxBuffer) -> AcceptanceFilter {
+        match buffer {
+            RxBuffer::RXB0 => AcceptanceFilter::Mask0,
+            RxBuffer::RXB1 => AcceptanceFilter::Mask1,
+        }
+    }
+
+    fn issue(&self, slot: AcceptanceFilter) -> Option<FilterIssue> {
+        let entries = self.filters.iter().filter(|(filter, _)| *filter == slot);
+        if entries.count() > 1 {
+            return Some(FilterIssue::Overwritten(slot));
+        }
+
+        let buffer = slot.receive_buffer();
+        let filters = SLOTS
+            .into_iter()
+            .filter(|filter| !filter.is_mask() && filter.receive_buffer() == buffer);
+        let mask = self.value(Self::mask_of(buffer));
+        let value = self.value(slot);
+
+        if self.receive_any[buffer as usize] {
+            return value
+                .filter(|_| !slot.is_mask())
+                .map(|_| FilterIssue::Unreachable(slot));
+        }
+
+        if slot.is_mask() {
+            let mut filters = filters;
+            let filters_set = filters.any(|filter| self.value(filter).is_some());
+            return (value.is_none() && filters_set).then_some(FilterIssue::MissingMask(slot));
+        }
+
+        match (mask, value) {
+            (Some(_), None) => Some(FilterIssue::UnsetFilter(slot)),
+            (Some(mask), Some(value)) => {
+                let masked = |header: IdHeader| {
+                    let mut bytes = header.into_bytes();
+                    for (byte, mask) in bytes.iter_mut().zip(mask.into_bytes()) {
+                        *byte &= mask;
+                    }
+                    // EXIDE is always compared
+                    bytes[1] |= header.into_bytes()[1] & 0b0000_1000;
+                    bytes
+                };
+                filters
+                    .take_while(|&filter| filter != slot)
+                    .filter_map(|filter| self.value(filter))
+                    .any(|earlier| masked(earlier) == masked(value))
+                    .then_some(FilterIssue::Redundant(slot))
+            }
+            _ => None,
+        }
+    }
+}
@@ -1,11 +1,53 @@
 use core::fmt::Debug;
 
-use embedded_can::{Frame, Id};
+use embedded_can::{ExtendedId, Frame, Id, StandardId};
 
 use crate::registers::DLC;
 use crate::IdHeader;
 
+/// Number of data bytes of a CAN 2.0 frame with the given DLC
+///
+/// DLC values 9 to 15 are valid on the bus but still mean 8 data bytes.
+///
+/// ```
+/// use mcp25xx::dlc_to_len;
+///
+/// assert_eq!(dlc_to_len(3), 3);
+/// assert_eq!(dlc_to_len(15), 8);
+/// ```
+#[inline]
+pub const fn dlc_to_len(dlc: u8) -> usize {
+    if dlc > 8 {
+        8
+    } else {
+        dlc as usize
+    }
+}
+
+/// DLC for a CAN 2.0 frame with `len` data bytes
+///
+/// Frames carry at most 8 bytes, longer lengths are clamped to a DLC of 8.
+///
+/// ```
+/// use mcp25xx::len_to_dlc;
+///
+/// assert_eq!(len_to_dlc(3), 3);
+/// assert_eq!(len_to_dlc(64), 8);
+/// ```
+#[inline]
+pub const fn len_to_dlc(len: usize) -> u8 {
+    if len > 8 {
+        8
+    } else {
+        len as u8
+    }
+}
+
 /// CAN frame with the same in memory representation as the registers of the CAN controller
+///
+/// The layout is fixed to the 8 data bytes of a receive or transmit buffer, which lets the
+/// driver read and load frames without copying. Code meant to support other controllers as well,
+/// e.g. with CAN FD, is best written against the [`Frame`] trait instead of this type.
 #[derive(Clone, Default)]
 #[repr(C)]
 pub struct CanFrame {
@@ -15,11 +57,149 @@ pub struct CanFrame {
 }
 
 impl CanFrame {
+    /// Start building a frame step by step
+    ///
+    /// ```
+    /// use embedded_can::Frame;
+    /// use mcp25xx::CanFrame;
+    ///
+    /// let data = CanFrame::builder().standard(0x123).data(&[1, 2, 3]).build().unwrap();
+    /// assert_eq!(data.data(), [1, 2, 3]);
+    ///
+    /// let remote = CanFrame::builder().extended(0x1ABCD).remote(4).build().unwrap();
+    /// assert!(remote.is_remote_frame());
+    /// assert_eq!(remote.dlc(), 4);
+    /// ```
+    pub fn builder() -> FrameBuilder {
+        FrameBuilder::default()
+    }
+
+    /// Bus arbitration priority, lower values win arbitration
+    ///
+    /// Sorting frames by it gives the order they would be transmitted in.
+    ///
+    /// ```
+    /// use embedded_can::{ExtendedId, Frame, StandardId};
+    /// use mcp25xx::CanFrame;
+    ///
+    /// let standard = CanFrame::new(StandardId::new(0x100).unwrap(), &[]).unwrap();
+    /// let extended = CanFrame::new(ExtendedId::new(0x100 << 18).unwrap(), &[]).unwrap();
+    /// assert!(standard.priority() < extended.priority());
+    /// ```
+    pub fn priority(&self) -> u32 {
+        let rtr = self.is_remote_frame() as u32;
+        match self.id() {
+            Id::Standard(id) => (id.as_raw() as u32) << 21 | rtr << 20,
+            Id::Extended(id) => {
+                let id = id.as_raw();
+                (id >> 18) << 21 | 1 << 20 | 1 << 19 | (id & 0x3FFFF) << 1 | rtr
+            }
+        }
+    }
+
+    /// Same frame with a different id, e.g. to forward it to another bus
+    ///
+    /// Data, DLC and the remote flag are kept. [`Id`] only holds valid identifiers,
+    /// so unlike [`Frame::new`] this cannot fail.
+    ///
+    /// ```
+    /// use embedded_can::{ExtendedId, Frame, StandardId};
+    /// use mcp25xx::CanFrame;
+    ///
+    /// let frame = CanFrame::new(StandardId::new(0x100).unwrap(), &[1, 2]).unwrap();
+    /// let forwarded = frame.with_id(ExtendedId::new(0x1800_0100).unwrap());
+    /// assert!(forwarded.is_extended());
+    /// assert_eq!(forwarded.data(), [1, 2]);
+    /// ```
+    pub fn with_id(mut self, id: impl Into<Id>) -> Self {
+        self.id_header = IdHeader::from(id.into());
+        self
+    }
+
+    /// Worst case time in microseconds the frame occupies the bus at `bitrate_hz`, rounded up
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bitrate_hz` is zero.
+    ///
+    /// ```
+    /// use embedded_can::{Frame, StandardId};
+    /// use mcp25xx::CanFrame;
+    ///
+    /// let frame = CanFrame::new(StandardId::new(0x100).unwrap(), &[0; 8]).unwrap();
+    /// assert_eq!(frame.transmission_time_us(500_000), 270);
+    /// ```
+    pub fn transmission_time_us(&self, bitrate_hz: u32) -> u32 {
+        // CRC delimiter, ACK slot and delimiter, end of frame and interframe space
+        const TRAILER_BITS: u32 = 1 + 2 + 7 + 3;
+        let header_bits = if self.is_extended() { 39 } else { 19 };
+        let stuffed = header_bits + 8 * self.data().len() as u32 + 15;
+        let bits = stuffed + (stuffed - 1) / 4 + TRAILER_BITS;
+        (bits * 1_000_000).div_ceil(bitrate_hz)
+    }
+
+    /// Heuristic for frames that lost data bytes on a marginal SPI connection
+    ///
+    /// Returns `true` for data frames whose last data byte is zero, which also matches zero padding.
+    pub fn looks_truncated(&self) -> bool {
+        !self.is_remote_frame() && self.data().last() == Some(&0)
+    }
+
+    /// Decode the registers of a receive buffer as read by
+    /// [`MCP25xx::read_rx_buffer_raw`](crate::MCP25xx::read_rx_buffer_raw)
+    ///
+    /// The DLC is taken over unchanged, values above 8 result in a frame
+    /// for which [`CanFrame::is_valid`] returns `false` and which carries 8 data bytes.
+    pub fn from_raw(bytes: &[u8; 13]) -> Self {
+        let mut data = [0; 8];
+        data.copy_from_slice(&bytes[5..]);
+        let mut frame = CanFrame {
+            id_header: IdHeader::from_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            dlc: DLC::from(bytes[4]),
+            data,
+        };
+        frame.take_rx_rtr();
+        frame
+    }
+
+    /// Copy the RTR bit of a received standard frame to the DLC register
+    ///
+    /// The controller reports it in the SRR bit of SIDL for standard frames and in the DLC
+    /// register only for extended frames, while transmit buffers take it from the DLC register
+    /// for both formats. Transmit buffers read back have no SRR bit, their DLC register is kept.
+    pub(crate) fn take_rx_rtr(&mut self) {
+        const SRR: u8 = 0b0001_0000;
+        if !self.id_header.exide() && self.id_header.sidl() & SRR != 0 {
+            self.dlc.set_rtr(true);
+        }
+    }
+
+    /// Whether the DLC is within the 8 data bytes of the frame
+    ///
+    /// Frames built through [`Frame::new`] or read by the driver are always valid.
+    /// DLC values 9 to 15 are allowed by CAN 2.0 and mean 8 data bytes, see [`dlc_to_len`].
+    /// [`Frame::dlc`] of an invalid frame returns the raw value, [`Frame::data`] the 8 bytes.
+    pub fn is_valid(&self) -> bool {
+        self.dlc() <= 8
+    }
+
     pub(crate) fn as_bytes(&self) -> &[u8; 13] {
         // SAFETY:
         // Frame is [repr(C)] without any padding bytes
         unsafe { &*(self as *const CanFrame as *const [u8; core::mem::size_of::<CanFrame>()]) }
     }
+
+    /// Id, DLC and data bytes as loaded into a transmit buffer
+    ///
+    /// Remote frames request `dlc` bytes but carry no data themselves.
+    pub(crate) fn tx_bytes(&self) -> &[u8] {
+        let data_len = if self.is_remote_frame() {
+            0
+        } else {
+            dlc_to_len(self.dlc.dlc())
+        };
+        &self.as_bytes()[0..5 + data_len]
+    }
 }
 
 impl Frame for CanFrame {
@@ -30,7 +210,7 @@ impl Frame for CanFrame {
 
         let mut frame = CanFrame {
             id_header: IdHeader::from(id.into()),
-            dlc: DLC::new().with_dlc(data.len() as u8),
+            dlc: DLC::new().with_dlc(len_to_dlc(data.len())),
             data: [0; 8],
         };
 
@@ -45,7 +225,7 @@ impl Frame for CanFrame {
         }
         Ok(CanFrame {
             id_header: IdHeader::from(id.into()),
-            dlc: DLC::new().with_dlc(dlc as u8).with_rtr(true),
+            dlc: DLC::new().with_dlc(len_to_dlc(dlc)).with_rtr(true),
             data: [0; 8],
         })
     }
@@ -71,7 +251,11 @@ impl Frame for CanFrame {
 
     #[inline]
     fn data(&self) -> &[u8] {
-        &self.data[0..self.dlc()]
+        // remote frames do not carry any data, whatever their DLC
+        if self.is_remote_frame() {
+            return &[];
+        }
+        &self.data[0..dlc_to_len(self.dlc.dlc())]
     }
 }
 
@@ -90,3 +274,94 @@ impl Debug for CanFrame {
             .finish()
     }
 }
+
+/// Reasons a [`FrameBuilder`] could not build a frame
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrameError {
+    /// No id was given
+    MissingId,
+    /// The raw id exceeds 11 bits for a standard or 29 bits for an extended frame
+    InvalidId,
+    /// More than 8 data bytes, or a remote frame requesting more than 8 bytes
+    TooLong,
+    /// Both data bytes and a remote DLC were given, remote frames carry no data
+    RemoteWithData,
+}
+
+/// Fluent alternative to [`Frame::new`] and [`Frame::new_remote`], see [`CanFrame::builder`]
+///
+/// Errors are kept until [`FrameBuilder::build`], the first one is returned.
+#[derive(Clone, Default)]
+pub struct FrameBuilder {
+    id: Option<Id>,
+    data: [u8; 8],
+    len: usize,
+    remote: Option<usize>,
+    error: Option<FrameError>,
+}
+
+impl FrameBuilder {
+    /// Use an already validated id
+    pub fn id(mut self, id: impl Into<Id>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Standard 11 bit id
+    pub fn standard(mut self, raw: u16) -> Self {
+        match StandardId::new(raw) {
+            Some(id) => self.id = Some(id.into()),
+            None => self.fail(FrameError::InvalidId),
+        }
+        self
+    }
+
+    /// Extended 29 bit id
+    pub fn extended(mut self, raw: u32) -> Self {
+        match ExtendedId::new(raw) {
+            Some(id) => self.id = Some(id.into()),
+            None => self.fail(FrameError::InvalidId),
+        }
+        self
+    }
+
+    /// Data bytes of a data frame, the DLC is their number
+    pub fn data(mut self, data: &[u8]) -> Self {
+        if data.len() > 8 {
+            self.fail(FrameError::TooLong);
+        } else {
+            self.data = [0; 8];
+            self.data[..data.len()].copy_from_slice(data);
+            self.len = data.len();
+        }
+        self
+    }
+
+    /// Build a remote frame requesting `dlc` bytes
+    pub fn remote(mut self, dlc: usize) -> Self {
+        if dlc > 8 {
+            self.fail(FrameError::TooLong);
+        } else {
+            self.remote = Some(dlc);
+        }
+        self
+    }
+
+    /// Data frame or remote frame, data frames without [`FrameBuilder::data`] carry no bytes
+    pub fn build(self) -> Result<CanFrame, FrameError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let id = self.id.ok_or(FrameError::MissingId)?;
+        let frame = match self.remote {
+            Some(_) if self.len > 0 => return Err(FrameError::RemoteWithData),
+            Some(dlc) => CanFrame::new_remote(id, dlc),
+            None => CanFrame::new(id, &self.data[..self.len]),
+        };
+        frame.map_err(|()| FrameError::TooLong)
+    }
+
+    fn fail(&mut self, error: FrameError) {
+        self.error.get_or_insert(error);
+    }
+}
@@ -1,7 +1,36 @@
 use embedded_can::{ExtendedId, Id, StandardId};
 
+use crate::AcceptanceFilter;
+
 /// Id header used in filters and masks
-#[derive(Copy, Clone, Default)]
+///
+/// Four bytes in the order the controller stores them, `SIDH`, `SIDL`, `EID8`, `EID0`:
+///
+/// | byte   | bit 7 | bit 6 | bit 5 | bit 4 | bit 3 | bit 2 | bit 1 | bit 0 |
+/// |--------|-------|-------|-------|-------|-------|-------|-------|-------|
+/// | `SIDH` | SID10 | SID9  | SID8  | SID7  | SID6  | SID5  | SID4  | SID3  |
+/// | `SIDL` | SID2  | SID1  | SID0  | -     | EXIDE | -     | EID17 | EID16 |
+/// | `EID8` | EID15 | EID14 | EID13 | EID12 | EID11 | EID10 | EID9  | EID8  |
+/// | `EID0` | EID7  | EID6  | EID5  | EID4  | EID3  | EID2  | EID1  | EID0  |
+///
+/// A standard id is stored in `SID10..SID0`.
+/// An extended id uses `SID10..SID0` for its 11 most significant bits
+/// and `EID17..EID0` for the remaining 18 bits, with `EXIDE` set.
+///
+/// ```
+/// use embedded_can::{ExtendedId, StandardId};
+/// use mcp25xx::IdHeader;
+///
+/// let header = IdHeader::from(StandardId::new(0x123).unwrap());
+/// assert_eq!([header.sidh(), header.sidl()], [0x24, 0x60]);
+///
+/// let header = IdHeader::from(ExtendedId::new(0x1234_5678).unwrap());
+/// assert_eq!(
+///     [header.sidh(), header.sidl(), header.eid8(), header.eid0()],
+///     [0x91, 0xA8, 0x56, 0x78]
+/// );
+/// ```
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
 #[repr(C)]
 pub struct IdHeader {
     sidh: u8,
@@ -11,8 +40,8 @@ pub struct IdHeader {
 }
 
 impl IdHeader {
-    #[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
-    #[cfg_attr(doc, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+    #[cfg(feature = "fast_instructions")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
     pub fn with_two_data_bytes(id: StandardId, bytes: [u8; 2]) -> Self {
         let id = id.as_raw();
         IdHeader {
@@ -23,6 +52,18 @@ impl IdHeader {
         }
     }
 
+    /// Header from the raw SIDH, SIDL, EID8 and EID0 register values
+    ///
+    /// Usable in `const` contexts, unlike the conversions from ids.
+    pub const fn from_bytes(bytes: [u8; 4]) -> Self {
+        IdHeader {
+            sidh: bytes[0],
+            sidl: bytes[1],
+            eid8: bytes[2],
+            eid0: bytes[3],
+        }
+    }
+
     pub fn id(&self) -> Id {
         if self.exide() {
             let id = ((self.sidh as u32) << 21)
@@ -41,6 +82,30 @@ impl IdHeader {
         }
     }
 
+    /// Standard id bits `SID10..SID3`
+    #[inline]
+    pub fn sidh(&self) -> u8 {
+        self.sidh
+    }
+
+    /// Standard id bits `SID2..SID0`, `EXIDE` flag and extended id bits `EID17..EID16`
+    #[inline]
+    pub fn sidl(&self) -> u8 {
+        self.sidl
+    }
+
+    /// Extended id bits `EID15..EID8`, or the first data byte for `IdHeader::with_two_data_bytes`
+    #[inline]
+    pub fn eid8(&self) -> u8 {
+        self.eid8
+    }
+
+    /// Extended id bits `EID7..EID0`, or the second data byte for `IdHeader::with_two_data_bytes`
+    #[inline]
+    pub fn eid0(&self) -> u8 {
+        self.eid0
+    }
+
     #[inline]
     pub(crate) fn exide(&self) -> bool {
         self.sidl & 0b0000_1000 > 0
@@ -92,3 +157,332 @@ impl core::fmt::Debug for IdHeader {
         f.debug_struct("IdHeader").field("id", &self.id()).finish()
     }
 }
+
+/// Filter and mask pair accepting a range of extended ids
+///
+/// Extended id filters only work if the EXIDE bit is set in the filter,
+/// otherwise the filter only matches standard frames. The headers produced
+/// here always set it, in the mask as well where the bit is ignored by the chip.
+///
+/// ```
+/// # use mcp25xx::doctesthelper::get_mcp25xx;
+/// use embedded_can::ExtendedId;
+/// use mcp25xx::{AcceptanceFilter, ExtendedFilter, MCP25xx};
+///
+/// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+///
+/// // accept the J1939 PGN 0xFEF1 from any source address
+/// let first = ExtendedId::new(0x18FE_F100).unwrap();
+/// let last = ExtendedId::new(0x18FE_F1FF).unwrap();
+/// let filter = ExtendedFilter::range(first, last);
+/// assert_eq!(filter.mask_bits(), 0x1FFF_FF00);
+///
+/// let mut config_mode = mcp25xx.enter_config_mode().unwrap();
+/// config_mode.set_filter(AcceptanceFilter::Mask0, filter.mask()).unwrap();
+/// config_mode.set_filter(AcceptanceFilter::Filter0, filter.filter()).unwrap();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExtendedFilter {
+    id: u32,
+    mask: u32,
+}
+
+impl ExtendedFilter {
+    /// Accept all ids which match `id` in the bits set in `mask`
+    ///
+    /// Mask bits beyond the 29 id bits are ignored.
+    pub fn new(id: ExtendedId, mask: u32) -> Self {
+        let mask = mask & ExtendedId::MAX.as_raw();
+        ExtendedFilter {
+            id: id.as_raw() & mask,
+            mask,
+        }
+    }
+
+    /// Smallest filter accepting all ids from `first` up to and including `last`
+    ///
+    /// Only ranges aligned to a power of two are matched exactly,
+    /// other ranges also accept some ids around them.
+    pub fn range(first: ExtendedId, last: ExtendedId) -> Self {
+        let diff = first.as_raw() ^ last.as_raw();
+        let free_bits = u32::BITS - diff.leading_zeros();
+        let mask = !((1u64 << free_bits) - 1) as u32;
+        ExtendedFilter::new(first, mask)
+    }
+
+    /// Raw mask bits, a set bit needs to match the filter
+    pub fn mask_bits(&self) -> u32 {
+        self.mask
+    }
+
+    /// Whether a frame with the given id passes this filter
+    pub fn matches(&self, id: Id) -> bool {
+        match id {
+            Id::Standard(_) => false,
+            Id::Extended(id) => id.as_raw() & self.mask == self.id,
+        }
+    }
+
+    /// Header to write to one of the filter registers
+    pub fn filter(&self) -> IdHeader {
+        // SAFETY:
+        // id is masked to 29 bits
+        unsafe { ExtendedId::new_unchecked(self.id) }.into()
+    }
+
+    /// Header to write to the mask register
+    pub fn mask(&self) -> IdHeader {
+        // SAFETY:
+        // mask is limited to 29 bits
+        unsafe { ExtendedId::new_unchecked(self.mask) }.into()
+    }
+}
+
+/// Filter for J1939 frames with a given parameter group number (PGN)
+///
+/// J1939 splits the 29 bit id into the priority (bits 28..26), the PGN (bits 25..8) and the
+/// source address (bits 7..0). For PDU1 groups, with a PDU format byte (bits 23..16) below 240,
+/// bits 15..8 hold the destination address instead of being part of the PGN.
+/// By default the filter accepts the PGN with any priority, source and destination address.
+///
+/// ```
+/// # use mcp25xx::doctesthelper::get_mcp25xx;
+/// use mcp25xx::{AcceptanceFilter, J1939Filter, MCP25xx};
+///
+/// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+///
+/// // Electronic Engine Controller 1 from the engine at source address 0
+/// let eec1 = J1939Filter::pgn(61444).source(0x00);
+///
+/// let mut config_mode = mcp25xx.enter_config_mode().unwrap();
+/// config_mode.set_filter(AcceptanceFilter::Mask0, eec1.mask()).unwrap();
+/// config_mode.set_filter(AcceptanceFilter::Filter0, eec1.filter()).unwrap();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct J1939Filter {
+    id: u32,
+    mask: u32,
+}
+
+impl J1939Filter {
+    /// Accept all frames of the PGN, bits beyond the 18 PGN bits are ignored
+    ///
+    /// For PDU1 groups the destination address byte of `pgn` is ignored as well.
+    pub fn pgn(pgn: u32) -> Self {
+        let pgn = pgn & 0x3_FFFF;
+        let pdu1 = ((pgn >> 8) as u8) < 240;
+        let mask = if pdu1 { 0x03FF_0000 } else { 0x03FF_FF00 };
+        J1939Filter {
+            id: (pgn << 8) & mask,
+            mask,
+        }
+    }
+
+    /// Only accept frames with the given priority, 0 (highest) to 7
+    pub fn priority(mut self, priority: u8) -> Self {
+        self.id = (self.id & !0x1C00_0000) | ((priority as u32 & 0b111) << 26);
+        self.mask |= 0x1C00_0000;
+        self
+    }
+
+    /// Only accept frames sent by the given source address
+    pub fn source(mut self, address: u8) -> Self {
+        self.id = (self.id & !0xFF) | address as u32;
+        self.mask |= 0xFF;
+        self
+    }
+
+    /// Only accept PDU1 frames sent to the given destination address
+    ///
+    /// PDU2 groups have no destination address, for them this does nothing.
+    pub fn destination(mut self, address: u8) -> Self {
+        if self.mask & 0xFF00 == 0 {
+            self.id |= (address as u32) << 8;
+            self.mask |= 0xFF00;
+        }
+        self
+    }
+
+    /// Whether a frame with the given id passes this filter
+    pub fn matches(&self, id: Id) -> bool {
+        self.extended_filter().matches(id)
+    }
+
+    /// Same filter as a generic [`ExtendedFilter`]
+    pub fn extended_filter(&self) -> ExtendedFilter {
+        // SAFETY:
+        // id only has bits of the 29 bit mask set
+        ExtendedFilter::new(unsafe { ExtendedId::new_unchecked(self.id) }, self.mask)
+    }
+
+    /// Header to write to one of the filter registers
+    pub fn filter(&self) -> IdHeader {
+        self.extended_filter().filter()
+    }
+
+    /// Header to write to the mask register
+    pub fn mask(&self) -> IdHeader {
+        self.extended_filter().mask()
+    }
+}
+
+/// Filters and masks of both receive buffers for a bus carrying standard and extended frames
+///
+/// The EXIDE bit of a filter selects the frame type it applies to, a standard filter never
+/// matches an extended frame and vice versa. The masks have no such bit, and each receive buffer
+/// only has one mask for all of its filters. On the MCP2515 and MCP25625, the EID bits of the mask
+/// are compared against the first two data bytes of standard frames, so a mask suitable for
+/// extended ids makes standard filters reject most standard frames.
+///
+/// To keep both apart, receive buffer 0 only accepts standard frames with a mask
+/// without EID bits, receive buffer 1 only accepts extended frames.
+///
+/// ```
+/// # use mcp25xx::doctesthelper::get_mcp25xx;
+/// use embedded_can::{ExtendedId, StandardId};
+/// use mcp25xx::registers::{RXB0CTRL, RXB1CTRL, RXM};
+/// use mcp25xx::{Config, MCP25xx, MixedFilter};
+///
+/// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+///
+/// // standard ids 0x100 to 0x10F and extended ids 0x18FE_F100 to 0x18FE_F1FF
+/// let filter = MixedFilter::new(
+///     StandardId::new(0x100).unwrap(),
+///     0x7F0,
+///     ExtendedId::new(0x18FE_F100).unwrap(),
+///     0x1FFF_FF00,
+/// );
+/// let filters = filter.filters();
+/// let config = Config::default()
+///     .receive_buffer_0(RXB0CTRL::default().with_rxm(RXM::Filter))
+///     .receive_buffer_1(RXB1CTRL::default().with_rxm(RXM::Filter))
+///     .filters(&filters);
+/// mcp25xx.apply_config(&config).unwrap();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MixedFilter {
+    standard: u16,
+    standard_mask: u16,
+    extended: ExtendedFilter,
+}
+
+impl MixedFilter {
+    /// Accept standard ids matching `standard` in the bits set in `standard_mask`
+    /// and extended ids matching `extended` in the bits set in `extended_mask`
+    ///
+    /// Mask bits beyond the id bits are ignored.
+    pub fn new(
+        standard: StandardId,
+        standard_mask: u16,
+        extended: ExtendedId,
+        extended_mask: u32,
+    ) -> Self {
+        let standard_mask = standard_mask & StandardId::MAX.as_raw();
+        MixedFilter {
+            standard: standard.as_raw() & standard_mask,
+            standard_mask,
+            extended: ExtendedFilter::new(extended, extended_mask),
+        }
+    }
+
+    /// Whether a frame with the given id passes these filters
+    pub fn matches(&self, id: Id) -> bool {
+        match id {
+            Id::Standard(id) => id.as_raw() & self.standard_mask == self.standard,
+            Id::Extended(_) => self.extended.matches(id),
+        }
+    }
+
+    /// All filters and masks, to be passed to [`Config::filters`](crate::Config::filters)
+    pub fn filters(&self) -> [(AcceptanceFilter, IdHeader); 8] {
+        use AcceptanceFilter::*;
+
+        // SAFETY:
+        // both values are masked to 11 bits
+        let (standard, standard_mask) = unsafe {
+            (
+                IdHeader::from(StandardId::new_unchecked(self.standard)),
+                IdHeader::from(StandardId::new_unchecked(self.standard_mask)),
+            )
+        };
+        let extended = self.extended.filter();
+        [
+            (Mask0, standard_mask),
+            (Filter0, standard),
+            (Filter1, standard),
+            (Mask1, self.extended.mask()),
+            (Filter2, extended),
+            (Filter3, extended),
+            (Filter4, extended),
+            (Filter5, extended),
+        ]
+    }
+}
+
+/// Filter and mask pair matching a standard id together with the first two data bytes
+///
+/// For standard frames the MCP2515 and MCP25625 compare the EID8 and EID0 bits of filter and mask
+/// against the first two data bytes. The id goes into SIDH and the upper three bits of SIDL, the
+/// data bytes into EID8 and EID0. EXIDE stays cleared, so the filter never matches extended frames.
+///
+/// Masks are shared by all filters of a receive buffer, the other filters of that buffer
+/// compare the data bytes as well.
+///
+/// ```
+/// # use mcp25xx::doctesthelper::get_mcp25xx;
+/// use embedded_can::StandardId;
+/// use mcp25xx::{AcceptanceFilter, DataByteFilter, MCP25xx};
+///
+/// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+///
+/// // id 0x123 with 0xFE in the first data byte, any second data byte
+/// let filter = DataByteFilter::new(StandardId::new(0x123).unwrap(), 0x7FF, [0xFE, 0x00], [0xFF, 0x00]);
+///
+/// // Filter3 feeds receive buffer 1, which uses Mask1
+/// let mut config_mode = mcp25xx.enter_config_mode().unwrap();
+/// config_mode.set_filter(AcceptanceFilter::Mask1, filter.mask()).unwrap();
+/// config_mode.set_filter(AcceptanceFilter::Filter3, filter.filter()).unwrap();
+/// ```
+#[cfg(feature = "fast_instructions")]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DataByteFilter {
+    id: u16,
+    id_mask: u16,
+    data: [u8; 2],
+    data_mask: [u8; 2],
+}
+
+#[cfg(feature = "fast_instructions")]
+impl DataByteFilter {
+    /// Accept standard frames matching `id` in the bits set in `id_mask`
+    /// and `data` in the bits set in `data_mask`
+    ///
+    /// Mask bits beyond the 11 id bits are ignored.
+    pub fn new(id: StandardId, id_mask: u16, data: [u8; 2], data_mask: [u8; 2]) -> Self {
+        let id_mask = id_mask & StandardId::MAX.as_raw();
+        DataByteFilter {
+            id: id.as_raw() & id_mask,
+            id_mask,
+            data: [data[0] & data_mask[0], data[1] & data_mask[1]],
+            data_mask,
+        }
+    }
+
+    /// Header to write to one of the filter registers
+    pub fn filter(&self) -> IdHeader {
+        // SAFETY:
+        // id is masked to 11 bits
+        IdHeader::with_two_data_bytes(unsafe { StandardId::new_unchecked(self.id) }, self.data)
+    }
+
+    /// Header to write to the mask register
+    pub fn mask(&self) -> IdHeader {
+        // SAFETY:
+        // mask is limited to 11 bits
+        IdHeader::with_two_data_bytes(
+            unsafe { StandardId::new_unchecked(self.id_mask) },
+            self.data_mask,
+        )
+    }
+}
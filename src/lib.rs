@@ -4,6 +4,35 @@
 //!
 //! Activating the `mcp2515` or `mcp25625` feature will enable
 //! additional registers and instructions the MCP2510 does not support.
+//! Both features enable the same instructions, so enabling both is fine.
+//! A build with either feature does not work on an MCP2510, use
+//! `MCP25xx::probe_fast_instructions` to detect this at runtime.
+//!
+//! The MCP25625 is a MCP2515 with an integrated transceiver. The transceiver is controlled
+//! by pins only: slope control is fixed, STBY needs a GPIO and SPLIT is not available,
+//! so from the driver's view both chips behave identically, see [`Capabilities`].
+//!
+//! The `sim` feature adds [`sim::SimController`], an in-memory stand-in
+//! for the CAN controller to test application code without hardware.
+//!
+//! The `socketcan` feature adds conversions between [`CanFrame`] and `socketcan::CanFrame`
+//! for sharing code with Linux hosts. It requires `std`.
+//!
+//! The `embedded-can-04` feature additionally implements the traits of `embedded-can` 0.4
+//! for [`MCP25xx`] and [`CanFrame`], re-exported as `embedded_can_04`.
+//! The `embedded-can` 0.3 implementations stay available, the methods map as follows:
+//!
+//! | `embedded-can` 0.3                   | `embedded-can` 0.4          |
+//! |--------------------------------------|-----------------------------|
+//! | `Can::try_transmit`                  | `nb::Can::transmit`         |
+//! | `Can::try_receive`                   | `nb::Can::receive`          |
+//! | `blocking::Can::write`               | `blocking::Can::transmit`   |
+//! | `blocking::Can::read`                | `blocking::Can::receive`    |
+//!
+//! The 0.4 traits report [`Error`]. SPI errors are wrapped in [`Error::Spi`], and with
+//! [`MCP25xx::with_strict_dlc`] enabled both `receive` methods fail with [`Error::MalformedFrame`]
+//! for frames with a DLC above 8, like [`MCP25xx::try_receive_checked`].
+//! With the `Frame` traits of both versions in scope, frame methods need fully qualified calls.
 //!
 //! # Example
 //!
@@ -15,12 +44,12 @@
 //! use mcp25xx::registers::{OperationMode, RXB0CTRL, RXM};
 //! use mcp25xx::{CanFrame, Config, MCP25xx};
 //!
-//! # let spi = NoOpSPI;
+//! # let spi = NoOpSPI::default();
 //! # let cs = NoOpCS;
 //! #
 //! // spi, cs and timer are structs implementing their respective embedded_hal traits.
 //!
-//! let mut mcp25xx = MCP25xx { spi, cs };
+//! let mut mcp25xx = MCP25xx::new(spi, cs);
 //!
 //! let config = Config::default()
 //!     .mode(OperationMode::NormalOperation)
@@ -43,16 +72,29 @@
 //! ```
 
 #![no_std]
-#![cfg_attr(doc, feature(doc_cfg))]
+#![cfg_attr(docsrs, feature(doc_cfg))]
 use core::fmt::Debug;
 
 pub use embedded_can;
-use embedded_can::Frame;
+use embedded_can::{ExtendedId, Frame, Id, StandardId};
+#[cfg(feature = "embedded-can-04")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embedded-can-04")))]
+pub use embedded_can_04;
+use embedded_hal::blocking::delay::DelayUs;
 
-pub use config::Config;
-pub use frame::CanFrame;
-pub use idheader::IdHeader;
-pub use spi_trait::SpiWithCs;
+pub use config::{Config, ConfigSet, StaticConfig};
+pub use config_mode::ConfigMode;
+pub use error::Error;
+pub use filter_plan::{FilterIssue, FilterPlan};
+pub use frame::{dlc_to_len, len_to_dlc, CanFrame, FrameBuilder, FrameError};
+#[cfg(feature = "fast_instructions")]
+pub use idheader::DataByteFilter;
+pub use idheader::{ExtendedFilter, IdHeader, J1939Filter, MixedFilter};
+pub use loopback::LoopbackSession;
+pub use software_filter::SoftwareFilterSet;
+pub use spi16::Spi16;
+pub use spi_trait::{ActiveHighCs, CsFn, NoCs, SpiWithCs};
+pub use tx_fifo::TxFifo;
 
 use crate::registers::*;
 
@@ -60,11 +102,26 @@ use crate::registers::*;
 pub mod bitrates;
 /// Register bitfields
 pub mod registers;
+/// In-memory CAN controller for host side testing
+#[cfg(feature = "sim")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sim")))]
+pub mod sim;
 
+#[cfg(feature = "embedded-can-04")]
+mod can_04;
 mod config;
+mod config_mode;
+mod error;
+mod filter_plan;
 mod frame;
 mod idheader;
+mod loopback;
+#[cfg(feature = "socketcan")]
+mod socketcan_frame;
+mod software_filter;
+mod spi16;
 mod spi_trait;
+mod tx_fifo;
 
 /// Either a MCP2510, MCP2515 or MCP25625 CAN controller
 ///
@@ -74,6 +131,157 @@ mod spi_trait;
 pub struct MCP25xx<SPI, CS> {
     pub spi: SPI,
     pub cs: CS,
+    error_state: ErrorState,
+    /// Next transmit buffer to try if round-robin is enabled
+    tx_cursor: Option<u8>,
+    /// Receive buffer operating modes to restore when leaving promiscuous mode
+    saved_rxm: Option<(RXM, RXM)>,
+    /// DLC of the last received frame before clamping to 8
+    last_raw_dlc: u8,
+    /// Whether the SPI bus returned fewer bytes than requested for the last received frame
+    last_rx_truncated: bool,
+    /// CANSTAT reads before a requested mode change is considered failed
+    mode_poll_limit: usize,
+    /// Filters and masks written through [`MCP25xx::set_filter`] since the last reset
+    filter_cache: [Option<IdHeader>; 8],
+    /// Repetitions of a failed read transaction
+    spi_retries: u8,
+    /// Whether received frames with a DLC above 8 are reported as errors
+    strict_dlc: bool,
+    /// Transmit requests which lost arbitration, see [`MCP25xx::retransmit_count`]
+    retransmit_count: u32,
+    /// Transmit buffers with an arbitration loss observed during the pending request
+    arbitration_lost: u8,
+    /// Operation mode to return to from [`MCP25xx::standby`]
+    standby_from: Option<OperationMode>,
+}
+
+impl<SPI, CS> MCP25xx<SPI, CS> {
+    /// Create a driver, assuming [`ErrorState::Active`], without any SPI traffic
+    pub fn new(spi: SPI, cs: CS) -> Self {
+        MCP25xx {
+            spi,
+            cs,
+            error_state: ErrorState::Active,
+            tx_cursor: None,
+            saved_rxm: None,
+            last_raw_dlc: 0,
+            last_rx_truncated: false,
+            mode_poll_limit: MODE_CHANGE_POLLS,
+            filter_cache: [None; 8],
+            spi_retries: 0,
+            strict_dlc: false,
+            retransmit_count: 0,
+            arbitration_lost: 0,
+            standby_from: None,
+        }
+    }
+
+    /// Whether the controller can acknowledge frames without ever transmitting otherwise
+    ///
+    /// Always `false`, none of the MCP25xx chips has a restricted operation mode.
+    /// [`OperationMode::ListenOnly`] sends no acknowledgements, normal mode also sends error frames.
+    pub const fn can_ack_silently() -> bool {
+        false
+    }
+
+    /// Features of the chip selected through the crate features
+    ///
+    /// ```
+    /// use mcp25xx::MCP25xx;
+    ///
+    /// let capabilities = MCP25xx::<(), ()>::capabilities();
+    /// assert!(!capabilities.transceiver_control);
+    /// ```
+    pub const fn capabilities() -> Capabilities {
+        Capabilities {
+            fast_instructions: cfg!(feature = "fast_instructions"),
+            integrated_transceiver: cfg!(feature = "mcp25625"),
+            transceiver_control: false,
+        }
+    }
+
+    /// Number of CANSTAT reads while waiting for a mode change, 100 by default
+    ///
+    /// Entering Configuration or Sleep mode waits for the frame on the bus to end,
+    /// low bitrates or fast SPI clocks need a higher limit.
+    pub fn set_mode_poll_limit(&mut self, polls: usize) {
+        self.mode_poll_limit = polls;
+    }
+
+    /// DLC of the last frame read from a receive buffer, as delivered by the controller
+    ///
+    /// The DLC field holds values up to 15, received frames clamp it to 8
+    /// unless [`MCP25xx::with_strict_dlc`] rejects them. Their data is always at most 8 bytes long, whatever the raw DLC was.
+    #[inline]
+    pub fn last_raw_dlc(&self) -> u8 {
+        self.last_raw_dlc
+    }
+
+    /// Whether the SPI bus delivered fewer bytes than requested for the last frame read
+    ///
+    /// See also [`CanFrame::looks_truncated`].
+    #[inline]
+    pub fn last_rx_truncated(&self) -> bool {
+        self.last_rx_truncated
+    }
+
+    /// Number of transmit requests which lost arbitration before they completed
+    ///
+    /// Only losses seen by [`MCP25xx::poll_transmit_complete`] and [`MCP25xx::send_blocking`]
+    /// are counted, so this is a lower bound. Wraps around on overflow.
+    #[inline]
+    pub fn retransmit_count(&self) -> u32 {
+        self.retransmit_count
+    }
+
+    /// Repeat read transactions failing on the SPI bus up to `retries` times, 0 by default
+    ///
+    /// Writes and receive buffer reads are never repeated, as they have side effects.
+    pub fn with_spi_retries(mut self, retries: u8) -> Self {
+        self.spi_retries = retries;
+        self
+    }
+
+    /// Report received frames with a DLC above 8 as [`Error::MalformedFrame`], disabled by default
+    ///
+    /// By default such frames have their DLC clamped to 8, see [`MCP25xx::last_raw_dlc`].
+    pub fn with_strict_dlc(mut self, strict: bool) -> Self {
+        self.strict_dlc = strict;
+        self
+    }
+
+    /// Distribute transmitted frames evenly across the free transmit buffers
+    ///
+    /// By default [`Can::try_transmit`](embedded_can::Can::try_transmit) always prefers the lowest free buffer.
+    /// With round-robin enabled, each call starts looking after the buffer used last.
+    pub fn with_tx_round_robin(mut self, enabled: bool) -> Self {
+        self.tx_cursor = enabled.then_some(0);
+        self
+    }
+
+    /// Pick a free transmit buffer and advance the round-robin cursor
+    fn next_tx_buffer(&mut self, status: ReadStatusResponse) -> Option<TxBuffer> {
+        let buf_idx = free_tx_buffer(status, self.tx_cursor.unwrap_or(0))?;
+        if let Some(cursor) = &mut self.tx_cursor {
+            *cursor = (buf_idx as u8 + 1) % 3;
+        }
+        Some(buf_idx)
+    }
+
+    /// Create a driver for boards where the CS line is active-high
+    ///
+    /// Standard boards have an active-low CS and should use [`MCP25xx::new`].
+    pub fn new_active_high_cs(spi: SPI, cs: CS) -> MCP25xx<SPI, ActiveHighCs<CS>> {
+        MCP25xx::new(spi, ActiveHighCs(cs))
+    }
+}
+
+impl<SPI> MCP25xx<SPI, NoCs> {
+    /// Create a driver which leaves chip select to the caller, see [`NoCs`]
+    pub fn new_external_cs(spi: SPI) -> Self {
+        MCP25xx::new(spi, NoCs)
+    }
 }
 
 impl<SPI, CS> MCP25xx<SPI, CS>
@@ -113,13 +321,88 @@ where
     /// ```
     pub fn apply_config(&mut self, config: &Config<'_>) -> Result<(), <Self as SpiWithCs>::Error> {
         self.reset()?;
+        self.write_config(config)
+    }
+
+    /// Like [`MCP25xx::apply_config`], but stays in Configuration mode
+    ///
+    /// All other bits of CANCTRL are applied. Registers not covered by [`Config`] can be set up
+    /// afterwards, [`MCP25xx::start`] then switches to the operation mode of `config`.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::bitrates::clock_16mhz::CNF_500K_BPS;
+    /// use mcp25xx::registers::{OperationMode, CANINTE};
+    /// use mcp25xx::{Config, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let config = Config::default()
+    ///     .mode(OperationMode::NormalOperation)
+    ///     .bitrate(CNF_500K_BPS);
+    /// mcp25xx.apply_config_staged(&config).unwrap();
+    /// mcp25xx
+    ///     .write_register(CANINTE::new().with_rx0ie(true))
+    ///     .unwrap();
+    /// mcp25xx.start(&config).unwrap();
+    /// ```
+    pub fn apply_config_staged(
+        &mut self,
+        config: &Config<'_>,
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.reset()?;
+        self.write_config_registers(config)?;
+        self.write_register(config.canctrl.with_reqop(OperationMode::Configuration))
+    }
+
+    /// Power-on initialization: waits for the oscillator, resets the controller and applies `config`
+    ///
+    /// Fails with [`Error::InvalidArgument`] if `config` has no bitrate, see [`Config::has_bitrate`].
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::{get_mcp25xx, NoOpDelay};
+    /// # use mcp25xx::{Config, MCP25xx};
+    /// # use mcp25xx::registers::OperationMode;
+    /// # use mcp25xx::bitrates::clock_16mhz::CNF_500K_BPS;
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    /// # let mut delay = NoOpDelay;
+    ///
+    /// let config = Config::default()
+    ///     .mode(OperationMode::NormalOperation)
+    ///     .bitrate(CNF_500K_BPS);
+    /// mcp25xx.init(&config, &mut delay).unwrap();
+    /// ```
+    pub fn init(
+        &mut self,
+        config: &Config<'_>,
+        delay: &mut impl DelayUs<u32>,
+    ) -> Result<(), Error<<Self as SpiWithCs>::Error>> {
+        if !config.has_bitrate() {
+            return Err(Error::InvalidArgument);
+        }
+        delay.delay_us(OSC_STARTUP_US);
+        self.reset_with_delay(delay).map_err(Error::Spi)?;
+        self.wait_for_mode(OperationMode::Configuration)?;
+        self.write_config(config).map_err(Error::Spi)
+    }
+
+    fn write_config(&mut self, config: &Config<'_>) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.write_config_registers(config)?;
+        self.write_register(config.canctrl)
+    }
+
+    /// Write everything but CANCTRL
+    fn write_config_registers(
+        &mut self,
+        config: &Config<'_>,
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
         self.set_bitrate(config.cnf)?;
         self.write_register(config.rxb0ctrl)?;
         self.write_register(config.rxb1ctrl)?;
         for &(filter, id_header) in config.filters {
             self.set_filter(filter, id_header)?;
         }
-        self.write_register(config.canctrl)
+        Ok(())
     }
 
     /// Set the controller to NormalOperation, Sleep, Loopback, ListenOnly or Configuration
@@ -128,20 +411,230 @@ where
         self.modify_register(reg, 0b11100000)
     }
 
+    /// Switch to the operation mode of `config` and wait until the controller confirms it
+    ///
+    /// Leaves all other registers untouched. Use it to go live after setting up
+    /// the registers manually, e.g. through [`MCP25xx::enter_config_mode`].
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::bitrates::clock_16mhz::CNF_500K_BPS;
+    /// use mcp25xx::registers::OperationMode;
+    /// use mcp25xx::{Config, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let config = Config::default().mode(OperationMode::NormalOperation);
+    /// let mut config_mode = mcp25xx.enter_config_mode().unwrap();
+    /// config_mode.set_bitrate(CNF_500K_BPS).unwrap();
+    /// drop(config_mode);
+    ///
+    /// mcp25xx.start(&config).unwrap();
+    /// ```
+    pub fn start(&mut self, config: &Config<'_>) -> Result<(), Error<<Self as SpiWithCs>::Error>> {
+        let mode = config.canctrl.reqop();
+        self.set_mode(mode).map_err(Error::Spi)?;
+        self.wait_for_mode(mode)
+    }
+
+    /// Switch to Configuration mode until the returned guard is dropped
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// # use mcp25xx::bitrates::clock_16mhz::CNF_500K_BPS;
+    /// use embedded_can::StandardId;
+    /// use mcp25xx::{AcceptanceFilter, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let mut config_mode = mcp25xx.enter_config_mode().unwrap();
+    /// config_mode.set_bitrate(CNF_500K_BPS).unwrap();
+    /// config_mode
+    ///     .set_masks(StandardId::MAX.into(), StandardId::MAX.into())
+    ///     .unwrap();
+    /// config_mode
+    ///     .set_filter(AcceptanceFilter::Filter0, StandardId::new(123).unwrap().into())
+    ///     .unwrap();
+    /// drop(config_mode);
+    /// ```
+    pub fn enter_config_mode(
+        &mut self,
+    ) -> Result<ConfigMode<'_, SPI, CS>, Error<<Self as SpiWithCs>::Error>> {
+        let canstat: CANSTAT = self.read_register().map_err(Error::Spi)?;
+        self.set_mode(OperationMode::Configuration)
+            .map_err(Error::Spi)?;
+        self.wait_for_mode(OperationMode::Configuration)?;
+        Ok(ConfigMode {
+            mcp25xx: self,
+            previous_mode: canstat.opmod(),
+        })
+    }
+
+    /// Stop taking part in bus traffic by switching to Configuration mode
+    ///
+    /// Unlike Sleep mode no wake-up is needed, [`MCP25xx::resume`] returns to the previous mode.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::MCP25xx;
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// mcp25xx.standby().unwrap();
+    /// // bus silent, e.g. during a measurement
+    /// mcp25xx.resume().unwrap();
+    /// ```
+    pub fn standby(&mut self) -> Result<(), Error<<Self as SpiWithCs>::Error>> {
+        if self.standby_from.is_some() {
+            return Ok(());
+        }
+        let canstat: CANSTAT = self.read_register().map_err(Error::Spi)?;
+        self.set_mode(OperationMode::Configuration)
+            .map_err(Error::Spi)?;
+        self.wait_for_mode(OperationMode::Configuration)?;
+        self.standby_from = Some(canstat.opmod());
+        Ok(())
+    }
+
+    /// Return to the operation mode [`MCP25xx::standby`] was called in
+    ///
+    /// Waits for the controller to confirm the mode change.
+    /// Fails with [`Error::InvalidArgument`] if not in standby.
+    pub fn resume(&mut self) -> Result<(), Error<<Self as SpiWithCs>::Error>> {
+        let mode = self.standby_from.ok_or(Error::InvalidArgument)?;
+        self.set_mode(mode).map_err(Error::Spi)?;
+        self.wait_for_mode(mode)?;
+        self.standby_from = None;
+        Ok(())
+    }
+
+    /// Switch to Loopback mode until the returned guard is dropped
+    ///
+    /// Discards frames still held by the receive buffers.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::{get_mcp25xx, NoOpDelay};
+    /// use embedded_can::{Frame, StandardId};
+    /// use mcp25xx::{CanFrame, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    /// # let mut delay = NoOpDelay;
+    ///
+    /// let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[1, 2, 3]).unwrap();
+    /// let mut session = mcp25xx.loopback_session().unwrap();
+    /// if let Ok(echo) = session.send_and_recv(&frame, &mut delay, 10_000) {
+    ///     assert_eq!(echo.data(), frame.data());
+    /// }
+    /// ```
+    pub fn loopback_session(
+        &mut self,
+    ) -> Result<LoopbackSession<'_, SPI, CS>, Error<<Self as SpiWithCs>::Error>> {
+        let canstat: CANSTAT = self.read_register().map_err(Error::Spi)?;
+        self.set_mode(OperationMode::Loopback).map_err(Error::Spi)?;
+        self.wait_for_mode(OperationMode::Loopback)?;
+        self.flush_rx().map_err(Error::Spi)?;
+        Ok(LoopbackSession {
+            mcp25xx: self,
+            previous_mode: canstat.opmod(),
+        })
+    }
+
+    /// Poll CANSTAT until the controller reports the requested operation mode
+    fn wait_for_mode(
+        &mut self,
+        mode: OperationMode,
+    ) -> Result<(), Error<<Self as SpiWithCs>::Error>> {
+        for _ in 0..self.mode_poll_limit {
+            let canstat: CANSTAT = self.read_register().map_err(Error::Spi)?;
+            if canstat.opmod() == mode {
+                return Ok(());
+            }
+        }
+        Err(Error::ModeChangeTimeout)
+    }
+
     /// Set clock settings
     ///
     /// See [`bitrates`] for preconfigured settings for different oscillator frequencies.
     ///
     /// ## Note:
-    /// The controller needs to be in Configuration Mode for this
+    /// The controller needs to be in Configuration Mode for this, see [`MCP25xx::enter_config_mode`]
     pub fn set_bitrate(&mut self, cnf: CNF) -> Result<(), <Self as SpiWithCs>::Error> {
         self.write_registers(CNF3::ADDRESS, &cnf.into_bytes())
     }
 
+    /// Change the Synchronization Jump Width and Baud Rate Prescaler of the current clock settings
+    ///
+    /// `sjw` is given in time quanta (1 to 4), `brp` is the raw register value (0 to 63).
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::{Error, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// mcp25xx.adjust_timing(2, 3).unwrap();
+    /// assert_eq!(mcp25xx.adjust_timing(5, 3), Err(Error::InvalidArgument));
+    /// ```
+    pub fn adjust_timing(
+        &mut self,
+        sjw: u8,
+        brp: u8,
+    ) -> Result<(), Error<<Self as SpiWithCs>::Error>> {
+        if !(1..=4).contains(&sjw) || brp > 63 {
+            return Err(Error::InvalidArgument);
+        }
+        // SJW and BRP make up the whole CNF1 register
+        let cnf1 = CNF1::new().with_sjw(sjw - 1).with_brp(brp);
+        let config_mode = self.enter_config_mode()?;
+        config_mode.mcp25xx.write_register(cnf1).map_err(Error::Spi)
+    }
+
+    /// Read the CNF registers and compute the bit timing for an oscillator running at `osc_hz`
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::MCP25xx;
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let timing = mcp25xx.read_bitrate_info(16_000_000).unwrap();
+    /// # assert_eq!(timing.time_quanta, 5);
+    /// ```
+    pub fn read_bitrate_info(
+        &mut self,
+        osc_hz: u32,
+    ) -> Result<bitrates::TimingInfo, <Self as SpiWithCs>::Error> {
+        let mut bytes = [0; 3];
+        self.read_registers(CNF3::ADDRESS, &mut bytes)?;
+        Ok(bitrates::TimingInfo::from_cnf(
+            CNF::from_bytes(bytes),
+            osc_hz,
+        ))
+    }
+
+    /// Whether the CNF registers were changed from their reset value, see [`CNF::is_reset_default`]
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::MCP25xx;
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// if !mcp25xx.is_bitrate_set().unwrap() {
+    ///     // forgot to call set_bitrate or apply a config with a bitrate
+    /// }
+    /// ```
+    pub fn is_bitrate_set(&mut self) -> Result<bool, <Self as SpiWithCs>::Error> {
+        let mut bytes = [0; 3];
+        self.read_registers(CNF3::ADDRESS, &mut bytes)?;
+        Ok(!CNF::from_bytes(bytes).is_reset_default())
+    }
+
     /// Set individual receive buffer filters or masks
     ///
     /// ## Note:
-    /// The controller needs to be in Configuration Mode for this
+    /// The controller needs to be in Configuration Mode for this, see [`MCP25xx::enter_config_mode`]
     ///
     /// ```
     /// # use mcp25xx::doctesthelper::get_mcp25xx;
@@ -157,7 +650,7 @@ where
     /// mcp25xx.set_filter(Filter0, IdHeader::from(std_id)).unwrap();
     ///
     /// mcp25xx.set_filter(Filter2, IdHeader::from(ext_id)).unwrap();
-    /// #[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
+    /// #[cfg(feature = "fast_instructions")]
     /// mcp25xx.set_filter(Filter3, IdHeader::with_two_data_bytes(std_id, [4, 5])).unwrap();
     ///
     /// ```
@@ -166,72 +659,830 @@ where
         filter: AcceptanceFilter,
         id: IdHeader,
     ) -> Result<(), <Self as SpiWithCs>::Error> {
-        self.write_registers(filter as u8, &id.into_bytes())
+        self.write_registers(filter as u8, &id.into_bytes())?;
+        self.filter_cache[filter.index()] = Some(id);
+        Ok(())
     }
 
-    /// Read status flags
-    pub fn read_status(&mut self) -> Result<ReadStatusResponse, <Self as SpiWithCs>::Error> {
-        self.set_cs_low();
-        self.spi_write(&[Instruction::ReadStatus as u8])?;
-        let mut buf = [0];
-        self.spi_transfer(&mut buf)?;
-        self.set_cs_high();
-        Ok(ReadStatusResponse::from_bytes(buf))
+    /// Change filters and masks with as little time in Configuration mode as possible
+    ///
+    /// Only entries differing from the values last written with [`MCP25xx::set_filter`] are written,
+    /// if nothing changed the mode is left alone. The node is off the bus while in Configuration mode,
+    /// the returned [`HotSwapReport`] gives the SPI bytes and the wait time spent in that window.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::{get_mcp25xx, NoOpDelay};
+    /// use embedded_can::StandardId;
+    /// use mcp25xx::{AcceptanceFilter, IdHeader, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    /// # let mut delay = NoOpDelay;
+    ///
+    /// let subscribe = |id| [
+    ///     (AcceptanceFilter::Mask0, IdHeader::from(StandardId::MAX)),
+    ///     (AcceptanceFilter::Filter0, IdHeader::from(StandardId::new(id).unwrap())),
+    /// ];
+    /// mcp25xx.hot_swap_filters(&subscribe(0x100), &mut delay).unwrap();
+    /// let report = mcp25xx.hot_swap_filters(&subscribe(0x200), &mut delay).unwrap();
+    /// assert_eq!(report.written, 1);
+    /// ```
+    pub fn hot_swap_filters(
+        &mut self,
+        filters: &[(AcceptanceFilter, IdHeader)],
+        delay: &mut impl DelayUs<u32>,
+    ) -> Result<HotSwapReport, Error<<Self as SpiWithCs>::Error>> {
+        let changed = |cache: &[Option<IdHeader>; 8], filter: AcceptanceFilter, id| {
+            cache[filter.index()] != Some(id)
+        };
+        if !filters
+            .iter()
+            .any(|&(filter, id)| changed(&self.filter_cache, filter, id))
+        {
+            return Ok(HotSwapReport::default());
+        }
+
+        let mut config_mode = self.enter_config_mode()?;
+        let previous_mode = config_mode.previous_mode();
+        let mut written = 0;
+        for &(filter, id) in filters {
+            if changed(&config_mode.mcp25xx.filter_cache, filter, id) {
+                config_mode.set_filter(filter, id).map_err(Error::Spi)?;
+                written += 1;
+            }
+        }
+        // requests the previous mode
+        drop(config_mode);
+
+        let mut mode_wait_us = 0;
+        for _ in 0..self.mode_poll_limit {
+            let canstat: CANSTAT = self.read_register().map_err(Error::Spi)?;
+            if canstat.opmod() == previous_mode {
+                return Ok(HotSwapReport {
+                    written,
+                    // 2 instruction and 4 id bytes per filter, BitModify of the mode
+                    off_bus_bytes: written as u32 * 6 + 4,
+                    mode_wait_us,
+                });
+            }
+            delay.delay_us(TRANSMIT_POLL_INTERVAL_US);
+            mode_wait_us += TRANSMIT_POLL_INTERVAL_US;
+        }
+        Err(Error::ModeChangeTimeout)
     }
 
-    /// Reset internal registers to the default state. Sets Configuration mode.
-    pub fn reset(&mut self) -> Result<(), <Self as SpiWithCs>::Error> {
-        self.set_cs_low();
-        self.spi_write(&[Instruction::Reset as u8])?;
-        self.set_cs_high();
-        Ok(())
+    /// Read back a receive buffer filter or mask
+    ///
+    /// ## Note:
+    /// Filters and masks read as zero outside of Configuration Mode, see [`MCP25xx::enter_config_mode`]
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use embedded_can::{ExtendedId, Id};
+    /// use mcp25xx::{AcceptanceFilter, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let id = ExtendedId::new(0x1234_5678).unwrap();
+    /// let mut config_mode = mcp25xx.enter_config_mode().unwrap();
+    /// config_mode.set_filter(AcceptanceFilter::Filter2, id.into()).unwrap();
+    /// let filter = config_mode.read_filter(AcceptanceFilter::Filter2).unwrap();
+    /// let latched = filter.id() == Id::Extended(id);
+    /// ```
+    pub fn read_filter(
+        &mut self,
+        filter: AcceptanceFilter,
+    ) -> Result<IdHeader, <Self as SpiWithCs>::Error> {
+        let mut bytes = [0; 4];
+        self.read_registers(filter as u8, &mut bytes)?;
+        Ok(IdHeader::from_bytes(bytes))
     }
 
-    /// Read receive buffer status flags
-    #[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
-    #[cfg_attr(doc, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
-    pub fn rx_status(&mut self) -> Result<RxStatusResponse, <Self as SpiWithCs>::Error> {
-        self.set_cs_low();
-        self.spi_write(&[Instruction::RxStatus as u8])?;
-        let mut buf = [0];
-        self.spi_transfer(&mut buf)?;
-        self.set_cs_high();
-        Ok(RxStatusResponse::from_bytes(buf))
+    /// Read back all six filters with two multi-register reads
+    ///
+    /// Filters 0 to 2 and filters 3 to 5 are stored in consecutive registers.
+    /// See [`MCP25xx::read_filter`] about Configuration mode.
+    pub fn read_all_filters(&mut self) -> Result<[IdHeader; 6], <Self as SpiWithCs>::Error> {
+        let mut bytes = [0; 24];
+        self.read_registers(AcceptanceFilter::Filter0 as u8, &mut bytes[..12])?;
+        self.read_registers(AcceptanceFilter::Filter3 as u8, &mut bytes[12..])?;
+        Ok(headers(bytes))
     }
-}
 
-impl<SPI, CS> embedded_can::Can for MCP25xx<SPI, CS>
-where
-    Self: SpiWithCs,
-{
-    type Frame = crate::frame::CanFrame;
-    type Error = <Self as SpiWithCs>::Error;
+    /// Read back both masks with a single multi-register read
+    ///
+    /// See [`MCP25xx::read_filter`] about Configuration mode.
+    pub fn read_all_masks(&mut self) -> Result<[IdHeader; 2], <Self as SpiWithCs>::Error> {
+        let mut bytes = [0; 8];
+        self.read_registers(AcceptanceFilter::Mask0 as u8, &mut bytes)?;
+        Ok(headers(bytes))
+    }
 
-    fn try_transmit(
+    /// Program the filters and mask of a receive buffer to accept the given ids
+    ///
+    /// If there are more distinct ids than filters, the mask is widened and other ids pass as well.
+    /// Returns whether exactly the given ids are accepted.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use embedded_can::{Id, StandardId};
+    /// use mcp25xx::{MCP25xx, RxBuffer};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let ids = [0x100, 0x200, 0x300].map(|id| Id::Standard(StandardId::new(id).unwrap()));
+    /// let exact = mcp25xx.accept_ids(RxBuffer::RXB1, &ids).unwrap();
+    /// assert!(exact);
+    /// ```
+    pub fn accept_ids(
         &mut self,
-        frame: &Self::Frame,
-    ) -> nb::Result<Option<Self::Frame>, <Self as SpiWithCs>::Error> {
-        let status = self.read_status()?;
-        let mut buf_idx = TxBuffer::TXB0;
-        if status.txreq0() {
-            buf_idx = TxBuffer::TXB1;
-            if status.txreq1() {
-                buf_idx = TxBuffer::TXB2;
-                if status.txreq2() {
-                    // TODO replace a pending lower priority frame
-                    return Err(nb::Error::WouldBlock);
+        buf_idx: RxBuffer,
+        ids: &[Id],
+    ) -> Result<bool, Error<<Self as SpiWithCs>::Error>> {
+        use AcceptanceFilter::*;
+        let (filters, mask_register): (&[AcceptanceFilter], _) = match buf_idx {
+            RxBuffer::RXB0 => (&[Filter0, Filter1], Mask0),
+            RxBuffer::RXB1 => (&[Filter2, Filter3, Filter4, Filter5], Mask1),
+        };
+
+        let extended = match ids.first() {
+            Some(id) => matches!(id, Id::Extended(_)),
+            None => return Err(Error::InvalidArgument),
+        };
+        if ids
+            .iter()
+            .any(|id| matches!(id, Id::Extended(_)) != extended)
+        {
+            return Err(Error::InvalidArgument);
+        }
+        let raw = |id: &Id| match id {
+            Id::Standard(id) => id.as_raw() as u32,
+            Id::Extended(id) => id.as_raw(),
+        };
+        let header = |raw: u32| -> IdHeader {
+            // SAFETY:
+            // raw is always derived from a valid id of the same kind
+            unsafe {
+                if extended {
+                    ExtendedId::new_unchecked(raw).into()
+                } else {
+                    StandardId::new_unchecked(raw as u16).into()
+                }
+            }
+        };
+        // distinct masked ids, as long as they fit into the filters
+        let distinct = |mask: u32| {
+            let mut values = [0; 4];
+            let mut count = 0;
+            for id in ids {
+                let value = raw(id) & mask;
+                if !values[..count].contains(&value) {
+                    if count == filters.len() {
+                        return None;
+                    }
+                    values[count] = value;
+                    count += 1;
                 }
             }
+            Some((values, count))
+        };
+
+        let full_mask = if extended { 0x1FFF_FFFF } else { 0x7FF };
+        let mut mask = full_mask;
+        let (values, count) = loop {
+            if let Some(distinct) = distinct(mask) {
+                break distinct;
+            }
+            // drop the least significant bit still in the mask,
+            // a mask of zero always yields a single value
+            mask &= mask - 1;
+        };
+
+        let mut config_mode = self.enter_config_mode()?;
+        config_mode
+            .set_filter(mask_register, header(mask))
+            .map_err(Error::Spi)?;
+        for (i, &filter) in filters.iter().enumerate() {
+            // unused filters repeat the first id
+            let value = if i < count { values[i] } else { values[0] };
+            config_mode
+                .set_filter(filter, header(value))
+                .map_err(Error::Spi)?;
         }
+        Ok(mask == full_mask)
+    }
 
-        self.load_tx_buffer(buf_idx, frame)?;
-        self.request_to_send(buf_idx)?;
-        Ok(None)
+    /// Read status flags
+    pub fn read_status(&mut self) -> Result<ReadStatusResponse, <Self as SpiWithCs>::Error> {
+        self.retry_read(|mcp25xx| {
+            mcp25xx.set_cs_low();
+            mcp25xx.spi_write(&[Instruction::ReadStatus as u8])?;
+            let mut buf = [0];
+            mcp25xx.spi_transfer(&mut buf)?;
+            mcp25xx.set_cs_high();
+            Ok(ReadStatusResponse::from_bytes(buf))
+        })
     }
 
-    fn try_receive(&mut self) -> nb::Result<Self::Frame, <Self as SpiWithCs>::Error> {
-        // TODO look at https://www.microchip.com/forums/tm.aspx?m=620741
-        let status = self.read_status()?;
+    /// Run a transaction without side effects, repeating it according to [`MCP25xx::with_spi_retries`]
+    fn retry_read<T>(
+        &mut self,
+        mut transaction: impl FnMut(&mut Self) -> Result<T, <Self as SpiWithCs>::Error>,
+    ) -> Result<T, <Self as SpiWithCs>::Error> {
+        let mut retries = self.spi_retries;
+        loop {
+            match transaction(self) {
+                Err(_) if retries > 0 => {
+                    // end the failed transaction before starting over
+                    self.set_cs_high();
+                    retries -= 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Number of transmit buffers without a pending transmit request (0 to 3)
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use embedded_can::{Can, Frame, StandardId};
+    /// use mcp25xx::{CanFrame, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    /// # let mut queue = [1u8, 2, 3].into_iter();
+    ///
+    /// // load as many queued frames as there are free buffers
+    /// for _ in 0..mcp25xx.free_tx_buffers().unwrap() {
+    ///     let Some(data) = queue.next() else { break };
+    ///     let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[data]).unwrap();
+    ///     mcp25xx.try_transmit(&frame).unwrap();
+    /// }
+    /// ```
+    pub fn free_tx_buffers(&mut self) -> Result<u8, <Self as SpiWithCs>::Error> {
+        let status = self.read_status()?;
+        Ok([status.txreq0(), status.txreq1(), status.txreq2()]
+            .into_iter()
+            .filter(|&txreq| !txreq)
+            .count() as u8)
+    }
+
+    /// Whether the transmit buffer still has a pending transmit request, from the TXREQ bit in [`MCP25xx::read_status`]
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use embedded_can::{Frame, StandardId};
+    /// use mcp25xx::{CanFrame, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let frame = CanFrame::new(StandardId::new(0x100).unwrap(), &[1]).unwrap();
+    /// let buf = mcp25xx.try_transmit_tracked(&frame).unwrap();
+    /// while mcp25xx.is_tx_pending(buf).unwrap() {}
+    /// ```
+    pub fn is_tx_pending(&mut self, buf_idx: TxBuffer) -> Result<bool, <Self as SpiWithCs>::Error> {
+        Ok(!tx_buffer_free(self.read_status()?, buf_idx))
+    }
+
+    /// Reset internal registers to the default state. Sets Configuration mode.
+    pub fn reset(&mut self) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.set_cs_low();
+        self.spi_write(&[Instruction::Reset as u8])?;
+        self.set_cs_high();
+        self.filter_cache = [None; 8];
+        self.standby_from = None;
+        self.saved_rxm = None;
+        Ok(())
+    }
+
+    /// Reset and wait [`RESET_DELAY_US`] until the controller accepts commands again
+    ///
+    /// [`MCP25xx::reset`] returns right after sending the instruction,
+    /// a command sent immediately afterwards may be lost while the controller restarts.
+    pub fn reset_with_delay(
+        &mut self,
+        delay: &mut impl DelayUs<u32>,
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.reset()?;
+        delay.delay_us(RESET_DELAY_US);
+        Ok(())
+    }
+
+    /// Receive buffers holding frames and the type of the frame reported by [`MCP25xx::rx_status`]
+    ///
+    /// Needs a single status byte. The controller only reports the type of one frame,
+    /// see [`PendingRx::message_type`].
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::{MCP25xx, RxBuffer};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let pending = mcp25xx.pending_rx().unwrap();
+    /// for buf in [RxBuffer::RXB0, RxBuffer::RXB1] {
+    ///     if pending.contains(buf) {
+    ///         let _frame = mcp25xx.read_rx_buffer(buf).unwrap();
+    ///     }
+    /// }
+    /// # assert_eq!(pending.count(), 0);
+    /// ```
+    #[cfg(feature = "fast_instructions")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+    pub fn pending_rx(&mut self) -> Result<PendingRx, <Self as SpiWithCs>::Error> {
+        Ok(PendingRx(self.rx_status()?))
+    }
+
+    /// Read receive buffer status flags
+    #[cfg(feature = "fast_instructions")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+    pub fn rx_status(&mut self) -> Result<RxStatusResponse, <Self as SpiWithCs>::Error> {
+        self.retry_read(|mcp25xx| {
+            mcp25xx.set_cs_low();
+            mcp25xx.spi_write(&[Instruction::RxStatus as u8])?;
+            let mut buf = [0];
+            mcp25xx.spi_transfer(&mut buf)?;
+            mcp25xx.set_cs_high();
+            Ok(RxStatusResponse::from_bytes(buf))
+        })
+    }
+
+    /// Check whether the controller understands the instructions enabled by the `mcp2515` and `mcp25625` features
+    ///
+    /// An MCP2510 silently ignores them. Fails with [`Error::Busy`] if TXB0 has a pending transmit request.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::MCP25xx;
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// if !mcp25xx.probe_fast_instructions().unwrap() {
+    ///     // running on an MCP2510, build without the mcp2515 feature
+    /// }
+    /// ```
+    #[cfg(feature = "fast_instructions")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+    pub fn probe_fast_instructions(&mut self) -> Result<bool, Error<<Self as SpiWithCs>::Error>> {
+        const PATTERNS: [[u8; 2]; 2] = [[0xA5, 0x5A], [0x5A, 0xA5]];
+
+        let txb0ctrl = self
+            .read_tx_buffer_control(TxBuffer::TXB0)
+            .map_err(Error::Spi)?;
+        if txb0ctrl.txreq() {
+            return Err(Error::Busy);
+        }
+        for pattern in PATTERNS {
+            self.set_cs_low();
+            // address pointer at TXB0D0
+            self.spi_write(&[Instruction::LoadTxBuffer as u8 | 1])
+                .map_err(Error::Spi)?;
+            self.spi_write(&pattern).map_err(Error::Spi)?;
+            self.set_cs_high();
+
+            let mut read_back = [0; 2];
+            self.read_registers_from(RegisterAddress::TXB0D0, &mut read_back)
+                .map_err(Error::Spi)?;
+            if read_back != pattern {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Check the SPI data lines by writing walking-1 and walking-0 patterns to a register
+    ///
+    /// Uses the first data byte of TXB0, fails with [`Error::Busy`] if TXB0 has a pending transmit request.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::MCP25xx;
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// if !mcp25xx.spi_integrity_test().unwrap() {
+    ///     // reject the board
+    /// }
+    /// ```
+    pub fn spi_integrity_test(&mut self) -> Result<bool, Error<<Self as SpiWithCs>::Error>> {
+        let txb0ctrl = self
+            .read_tx_buffer_control(TxBuffer::TXB0)
+            .map_err(Error::Spi)?;
+        if txb0ctrl.txreq() {
+            return Err(Error::Busy);
+        }
+        let walking_one = (0..8).map(|bit| 1u8 << bit);
+        for pattern in walking_one.clone().chain(walking_one.map(|p| !p)) {
+            self.write_registers_to(RegisterAddress::TXB0D0, &[pattern])
+                .map_err(Error::Spi)?;
+            let mut read_back = [0];
+            self.read_registers_from(RegisterAddress::TXB0D0, &mut read_back)
+                .map_err(Error::Spi)?;
+            if read_back[0] != pattern {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Transmit a frame in One-Shot mode, repeating failed attempts up to `max_attempts` times
+    ///
+    /// An attempt not ending within `timeout_us` microseconds is aborted and fails with [`Error::Timeout`].
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::{get_mcp25xx, NoOpDelay};
+    /// use embedded_can::{Frame, StandardId};
+    /// use mcp25xx::{CanFrame, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    /// # let mut delay = NoOpDelay;
+    ///
+    /// let frame = CanFrame::new(StandardId::new(123).unwrap(), &[1, 2, 3]).unwrap();
+    /// mcp25xx.try_write_bounded_retry(&frame, 3, &mut delay, 10_000).unwrap();
+    /// ```
+    #[cfg(feature = "fast_instructions")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+    pub fn try_write_bounded_retry(
+        &mut self,
+        frame: &CanFrame,
+        max_attempts: u8,
+        delay: &mut impl DelayUs<u32>,
+        timeout_us: u32,
+    ) -> nb::Result<(), Error<<Self as SpiWithCs>::Error>> {
+        if max_attempts == 0 {
+            return Err(nb::Error::Other(Error::InvalidArgument));
+        }
+        let status = self.read_status().map_err(Error::Spi)?;
+        let buf_idx = self.next_tx_buffer(status).ok_or(nb::Error::WouldBlock)?;
+
+        let result = self
+            .with_one_shot(|mcp25xx| {
+                mcp25xx.transmit_with_retries(buf_idx, frame, max_attempts, delay, timeout_us)
+            })
+            .map_err(Error::Spi)?;
+        Ok(result?)
+    }
+
+    /// Transmit a frame with a single attempt in One-Shot mode and report the outcome
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::{get_mcp25xx, NoOpDelay};
+    /// use embedded_can::{Frame, StandardId};
+    /// use mcp25xx::{CanFrame, MCP25xx, OneShotResult};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    /// # let mut delay = NoOpDelay;
+    ///
+    /// let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[1, 2, 3]).unwrap();
+    /// match mcp25xx.one_shot_send(&frame, &mut delay, 10_000).unwrap() {
+    ///     OneShotResult::Sent => {}
+    ///     OneShotResult::ArbitrationLost => { /* send it again in the next cycle */ }
+    ///     OneShotResult::Error | OneShotResult::Timeout => { /* check the bus */ }
+    /// }
+    /// ```
+    #[cfg(feature = "fast_instructions")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+    pub fn one_shot_send(
+        &mut self,
+        frame: &CanFrame,
+        delay: &mut impl DelayUs<u32>,
+        timeout_us: u32,
+    ) -> Result<OneShotResult, Error<<Self as SpiWithCs>::Error>> {
+        self.with_one_shot(|mcp25xx| mcp25xx.one_shot_attempt(frame, delay, timeout_us))
+            .map_err(Error::Spi)?
+    }
+
+    /// Receive a frame like [`Can::try_receive`](embedded_can::Can::try_receive),
+    /// honouring [`MCP25xx::with_strict_dlc`]
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::{Error, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx().with_strict_dlc(true);
+    ///
+    /// match mcp25xx.try_receive_checked() {
+    ///     Ok(frame) => { /* handle the frame */ }
+    ///     Err(nb::Error::WouldBlock) => { /* nothing received */ }
+    ///     Err(nb::Error::Other(Error::MalformedFrame)) => { /* a node sent a DLC above 8 */ }
+    ///     Err(nb::Error::Other(err)) => panic!("{:?}", err),
+    /// }
+    /// ```
+    pub fn try_receive_checked(
+        &mut self,
+    ) -> nb::Result<CanFrame, Error<<Self as SpiWithCs>::Error>> {
+        let frame = embedded_can::Can::try_receive(self).map_err(|e| e.map(Error::Spi))?;
+        if self.strict_dlc && self.last_raw_dlc > 8 {
+            return Err(nb::Error::Other(Error::MalformedFrame));
+        }
+        Ok(frame)
+    }
+
+    /// Wait up to `timeout_us` microseconds for a received frame
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::{get_mcp25xx, NoOpDelay};
+    /// use mcp25xx::{Error, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    /// # let mut delay = NoOpDelay;
+    ///
+    /// match mcp25xx.receive_with_timeout(&mut delay, 10_000) {
+    ///     Ok(frame) => { /* handle the frame */ }
+    ///     Err(Error::Timeout) => { /* nothing received within 10 ms */ }
+    ///     Err(err) => panic!("{:?}", err),
+    /// }
+    /// ```
+    pub fn receive_with_timeout(
+        &mut self,
+        delay: &mut impl DelayUs<u32>,
+        timeout_us: u32,
+    ) -> Result<CanFrame, Error<<Self as SpiWithCs>::Error>> {
+        let mut waited = 0;
+        loop {
+            match self.try_receive_checked() {
+                Ok(frame) => return Ok(frame),
+                Err(nb::Error::WouldBlock) => {}
+                Err(nb::Error::Other(err)) => return Err(err),
+            }
+            if !poll_delay(delay, RECEIVE_POLL_INTERVAL_US, &mut waited, timeout_us) {
+                return Err(Error::Timeout);
+            }
+        }
+    }
+
+    /// Transmit a frame and wait up to `timeout_us` microseconds until it was sent
+    ///
+    /// On [`Error::TransmitFailed`] or [`Error::Timeout`] the transmit request is aborted.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::{get_mcp25xx, NoOpDelay};
+    /// use embedded_can::{Frame, StandardId};
+    /// use mcp25xx::{CanFrame, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    /// # let mut delay = NoOpDelay;
+    ///
+    /// let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[1, 2, 3]).unwrap();
+    /// mcp25xx.send_blocking(&frame, &mut delay, 10_000).unwrap();
+    /// ```
+    pub fn send_blocking(
+        &mut self,
+        frame: &CanFrame,
+        delay: &mut impl DelayUs<u32>,
+        timeout_us: u32,
+    ) -> Result<(), Error<<Self as SpiWithCs>::Error>> {
+        let mut waited = 0;
+        let buf_idx = loop {
+            match self.try_transmit_tracked(frame) {
+                Ok(buf_idx) => break buf_idx,
+                Err(nb::Error::WouldBlock) => {}
+                Err(nb::Error::Other(err)) => return Err(Error::Spi(err)),
+            }
+            if !poll_delay(delay, TRANSMIT_POLL_INTERVAL_US, &mut waited, timeout_us) {
+                return Err(Error::Timeout);
+            }
+        };
+        loop {
+            let ctrl = self.read_tx_buffer_control(buf_idx).map_err(Error::Spi)?;
+            self.track_arbitration_loss(buf_idx, ctrl);
+            if !ctrl.txreq() {
+                self.clear_interrupt(tx_interrupt(buf_idx))
+                    .map_err(Error::Spi)?;
+                // MLOA stays set after a retransmission which won arbitration
+                if ctrl.txerr() || ctrl.abtf() {
+                    return Err(Error::TransmitFailed);
+                }
+                return Ok(());
+            }
+            let failed = ctrl.txerr();
+            if failed || !poll_delay(delay, TRANSMIT_POLL_INTERVAL_US, &mut waited, timeout_us) {
+                self.abort_tx_buffer(buf_idx).map_err(Error::Spi)?;
+                return Err(if failed {
+                    Error::TransmitFailed
+                } else {
+                    Error::Timeout
+                });
+            }
+        }
+    }
+
+    /// Estimate the bus load in percent by receiving frames for `window_ms` milliseconds
+    ///
+    /// A rough estimate: only frames passing the filters are seen and received frames are consumed.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::{get_mcp25xx, NoOpDelay};
+    /// use mcp25xx::MCP25xx;
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    /// # let mut delay = NoOpDelay;
+    ///
+    /// mcp25xx.set_promiscuous(true).unwrap();
+    /// let load = mcp25xx.estimate_bus_load(&mut delay, 100, 500_000).unwrap();
+    /// mcp25xx.set_promiscuous(false).unwrap();
+    /// # assert_eq!(load, 0);
+    /// ```
+    pub fn estimate_bus_load(
+        &mut self,
+        delay: &mut impl DelayUs<u32>,
+        window_ms: u32,
+        bitrate_hz: u32,
+    ) -> Result<u8, Error<<Self as SpiWithCs>::Error>> {
+        if window_ms == 0 || bitrate_hz == 0 {
+            return Err(Error::InvalidArgument);
+        }
+        let window_us = window_ms.saturating_mul(1000);
+        let mut waited = 0;
+        let mut busy_us: u64 = 0;
+        loop {
+            loop {
+                match embedded_can::Can::try_receive(self) {
+                    Ok(frame) => busy_us += frame.transmission_time_us(bitrate_hz) as u64,
+                    Err(nb::Error::WouldBlock) => break,
+                    Err(nb::Error::Other(err)) => return Err(Error::Spi(err)),
+                }
+            }
+            if !poll_delay(delay, RECEIVE_POLL_INTERVAL_US, &mut waited, window_us) {
+                break;
+            }
+        }
+        Ok(core::cmp::min(busy_us * 100 / window_us as u64, 100) as u8)
+    }
+
+    /// Send a remote frame and wait for the data frame answering it
+    ///
+    /// Other frames received while waiting are dropped.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::{get_mcp25xx, NoOpDelay};
+    /// use embedded_can::StandardId;
+    /// use mcp25xx::{Error, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    /// # let mut delay = NoOpDelay;
+    ///
+    /// let id = StandardId::new(0x321).unwrap();
+    /// match mcp25xx.request_data(id, 4, &mut delay, 10_000) {
+    ///     Ok(response) => { /* handle the data */ }
+    ///     Err(Error::Timeout) => { /* no node answered */ }
+    ///     Err(err) => panic!("{:?}", err),
+    /// }
+    /// ```
+    pub fn request_data(
+        &mut self,
+        id: impl Into<Id>,
+        dlc: usize,
+        delay: &mut impl DelayUs<u32>,
+        timeout_us: u32,
+    ) -> Result<CanFrame, Error<<Self as SpiWithCs>::Error>> {
+        let id = id.into();
+        let request = CanFrame::new_remote(id, dlc).map_err(|_| Error::InvalidArgument)?;
+        self.send_blocking(&request, delay, timeout_us)?;
+
+        let mut waited = 0;
+        loop {
+            match self.receive_filtered(|frame| frame.is_data_frame() && frame.id() == id) {
+                Ok(frame) => return Ok(frame),
+                Err(nb::Error::WouldBlock) => {}
+                Err(nb::Error::Other(err)) => return Err(Error::Spi(err)),
+            }
+            if !poll_delay(delay, RECEIVE_POLL_INTERVAL_US, &mut waited, timeout_us) {
+                return Err(Error::Timeout);
+            }
+        }
+    }
+
+    /// Clear the transmit request of the selected buffer, a transmission in progress still completes
+    fn abort_tx_buffer(&mut self, buf_idx: TxBuffer) -> Result<(), <Self as SpiWithCs>::Error> {
+        const TXREQ: u8 = 0b0000_1000;
+        match buf_idx {
+            TxBuffer::TXB0 => self.modify_register(TXB0CTRL::new().with_txreq(false), TXREQ),
+            TxBuffer::TXB1 => self.modify_register(TXB1CTRL::new().with_txreq(false), TXREQ),
+            TxBuffer::TXB2 => self.modify_register(TXB2CTRL::new().with_txreq(false), TXREQ),
+        }
+    }
+
+    /// Run `f` in One-Shot mode and restore the previous OSM bit of CANCTRL afterwards
+    #[cfg(feature = "fast_instructions")]
+    fn with_one_shot<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> T,
+    ) -> Result<T, <Self as SpiWithCs>::Error> {
+        const OSM: u8 = 0b0000_1000;
+
+        let canctrl: CANCTRL = self.read_register()?;
+        self.modify_register(CANCTRL::new().with_osm(true), OSM)?;
+        let result = f(self);
+        self.modify_register(CANCTRL::new().with_osm(canctrl.osm()), OSM)?;
+        Ok(result)
+    }
+
+    /// Count a transmit request once it completes if it lost arbitration in a poll before
+    fn track_arbitration_loss(&mut self, buf_idx: TxBuffer, ctrl: TXB0CTRL) {
+        let bit = 1 << buf_idx as u8;
+        if ctrl.mloa() {
+            self.arbitration_lost |= bit;
+        }
+        if !ctrl.txreq() && self.arbitration_lost & bit != 0 {
+            self.arbitration_lost &= !bit;
+            self.retransmit_count = self.retransmit_count.wrapping_add(1);
+        }
+    }
+
+    #[cfg(feature = "fast_instructions")]
+    fn transmit_with_retries(
+        &mut self,
+        buf_idx: TxBuffer,
+        frame: &CanFrame,
+        max_attempts: u8,
+        delay: &mut impl DelayUs<u32>,
+        timeout_us: u32,
+    ) -> Result<(), Error<<Self as SpiWithCs>::Error>> {
+        // the buffer keeps its content, so it only needs to be loaded once
+        self.load_tx_buffer(buf_idx, frame).map_err(Error::Spi)?;
+        for _ in 0..max_attempts {
+            self.request_to_send(buf_idx).map_err(Error::Spi)?;
+            // in One-Shot mode TXREQ gets cleared after a single attempt
+            let mut waited = 0;
+            let ctrl = loop {
+                let ctrl = self.read_tx_buffer_control(buf_idx).map_err(Error::Spi)?;
+                if !ctrl.txreq() {
+                    break ctrl;
+                }
+                if !poll_delay(delay, TRANSMIT_POLL_INTERVAL_US, &mut waited, timeout_us) {
+                    self.abort_tx_buffer(buf_idx).map_err(Error::Spi)?;
+                    return Err(Error::Timeout);
+                }
+            };
+            self.clear_interrupt(tx_interrupt(buf_idx))
+                .map_err(Error::Spi)?;
+            if !(ctrl.txerr() || ctrl.mloa() || ctrl.abtf()) {
+                return Ok(());
+            }
+        }
+        Err(Error::RetriesExhausted)
+    }
+
+    #[cfg(feature = "fast_instructions")]
+    fn one_shot_attempt(
+        &mut self,
+        frame: &CanFrame,
+        delay: &mut impl DelayUs<u32>,
+        timeout_us: u32,
+    ) -> Result<OneShotResult, Error<<Self as SpiWithCs>::Error>> {
+        let mut waited = 0;
+        let buf_idx = loop {
+            match self.try_transmit_tracked(frame) {
+                Ok(buf_idx) => break buf_idx,
+                Err(nb::Error::WouldBlock) => {}
+                Err(nb::Error::Other(err)) => return Err(Error::Spi(err)),
+            }
+            if !poll_delay(delay, TRANSMIT_POLL_INTERVAL_US, &mut waited, timeout_us) {
+                return Ok(OneShotResult::Timeout);
+            }
+        };
+        loop {
+            let ctrl = self.read_tx_buffer_control(buf_idx).map_err(Error::Spi)?;
+            if !ctrl.txreq() {
+                let result = if ctrl.mloa() {
+                    OneShotResult::ArbitrationLost
+                } else if ctrl.txerr() || ctrl.abtf() {
+                    OneShotResult::Error
+                } else {
+                    OneShotResult::Sent
+                };
+                self.clear_interrupt(tx_interrupt(buf_idx))
+                    .map_err(Error::Spi)?;
+                return Ok(result);
+            }
+            if !poll_delay(delay, TRANSMIT_POLL_INTERVAL_US, &mut waited, timeout_us) {
+                self.abort_tx_buffer(buf_idx).map_err(Error::Spi)?;
+                return Ok(OneShotResult::Timeout);
+            }
+        }
+    }
+}
+
+impl<SPI, CS> embedded_can::Can for MCP25xx<SPI, CS>
+where
+    Self: SpiWithCs,
+{
+    type Frame = crate::frame::CanFrame;
+    type Error = <Self as SpiWithCs>::Error;
+
+    fn try_transmit(
+        &mut self,
+        frame: &Self::Frame,
+    ) -> nb::Result<Option<Self::Frame>, <Self as SpiWithCs>::Error> {
+        // replacing pending frames is left to MCP25xx::try_transmit_preemptive,
+        // aborting costs several transactions and may be undesired
+        self.try_transmit_tracked(frame)?;
+        Ok(None)
+    }
+
+    fn try_receive(&mut self) -> nb::Result<Self::Frame, <Self as SpiWithCs>::Error> {
+        // TODO look at https://www.microchip.com/forums/tm.aspx?m=620741
+        let status = self.read_status()?;
         if status.rx0if() {
             Ok(self.read_rx_buffer(RxBuffer::RXB0)?)
         } else if status.rx1if() {
@@ -240,146 +1491,964 @@ where
             Err(nb::Error::WouldBlock)
         }
     }
-}
+}
+
+impl<SPI, CS> embedded_can::blocking::Default for MCP25xx<SPI, CS> where Self: SpiWithCs {}
+
+impl<SPI, CS> MCP25xx<SPI, CS>
+where
+    Self: SpiWithCs,
+{
+    /// Read a single register
+    pub fn read_register<R: Register>(&mut self) -> Result<R, <Self as SpiWithCs>::Error> {
+        let mut reg = [0];
+        self.read_registers(R::ADDRESS, &mut reg)?;
+        Ok(reg[0].into())
+    }
+
+    /// Write a single register
+    pub fn write_register<R: Register + Into<u8>>(
+        &mut self,
+        reg: R,
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.set_cs_low();
+        self.spi_write(&[Instruction::Write as u8, R::ADDRESS, reg.into()])?;
+        self.set_cs_high();
+        Ok(())
+    }
+
+    /// Modify a single register
+    ///
+    /// Only registers implementing [`Modify`] support the `Modify` Instruction
+    pub fn modify_register<R: Register + Modify + Into<u8>>(
+        &mut self,
+        reg: R,
+        mask: u8,
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.set_cs_low();
+        self.spi_write(&[Instruction::BitModify as u8, R::ADDRESS, mask, reg.into()])?;
+        self.set_cs_high();
+        Ok(())
+    }
+
+    /// Read multiple consecutive registers
+    ///
+    /// See [`MCP25xx::read_registers_from`] for a named start address.
+    pub fn read_registers(
+        &mut self,
+        start_address: u8,
+        buf: &mut [u8],
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.retry_read(|mcp25xx| {
+            mcp25xx.set_cs_low();
+            mcp25xx.spi_write(&[Instruction::Read as u8, start_address])?;
+            mcp25xx.spi_transfer(buf)?;
+            mcp25xx.set_cs_high();
+            Ok(())
+        })
+    }
+
+    /// Read multiple consecutive registers starting at a named address
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::registers::RegisterAddress;
+    /// use mcp25xx::MCP25xx;
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// // CANINTF and EFLG
+    /// let mut flags = [0; 2];
+    /// mcp25xx.read_registers_from(RegisterAddress::CANINTF, &mut flags).unwrap();
+    /// ```
+    #[inline]
+    pub fn read_registers_from(
+        &mut self,
+        start: RegisterAddress,
+        buf: &mut [u8],
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.read_registers(start.into(), buf)
+    }
+
+    /// Write multiple consecutive registers
+    ///
+    /// See [`MCP25xx::write_registers_to`] for a named start address.
+    pub fn write_registers(
+        &mut self,
+        start_address: u8,
+        data: &[u8],
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.set_cs_low();
+        self.spi_write(&[Instruction::Write as u8, start_address])?;
+        self.spi_write(data)?;
+        self.set_cs_high();
+        Ok(())
+    }
+
+    /// Write multiple consecutive registers starting at a named address
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::registers::RegisterAddress;
+    /// use mcp25xx::MCP25xx;
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// // first two data bytes of TXB1
+    /// mcp25xx.write_registers_to(RegisterAddress::TXB1D0, &[0x12, 0x34]).unwrap();
+    /// ```
+    #[inline]
+    pub fn write_registers_to(
+        &mut self,
+        start: RegisterAddress,
+        data: &[u8],
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.write_registers(start.into(), data)
+    }
+
+    /// Write a precomputed list of `(address, value)` pairs in the given order
+    ///
+    /// Runs of consecutive addresses share a single Write instruction, nothing is read back.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::MCP25xx;
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// // CNF3, CNF2 and CNF1 for 500 kbps with a 16 MHz oscillator, then Normal mode
+    /// const IMAGE: [(u8, u8); 4] = [(0x28, 0x86), (0x29, 0xF0), (0x2A, 0x00), (0x0F, 0x00)];
+    /// mcp25xx.reset().unwrap();
+    /// mcp25xx.apply_register_image(&IMAGE).unwrap();
+    /// ```
+    pub fn apply_register_image(
+        &mut self,
+        image: &[(u8, u8)],
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        let mut next_address = None;
+        for &(address, value) in image {
+            if next_address != Some(address) {
+                if next_address.is_some() {
+                    self.set_cs_high();
+                }
+                self.set_cs_low();
+                self.spi_write(&[Instruction::Write as u8, address])?;
+            }
+            self.spi_write(&[value])?;
+            next_address = Some(address.wrapping_add(1));
+        }
+        if next_address.is_some() {
+            self.set_cs_high();
+        }
+        Ok(())
+    }
+
+    /// Read the whole register map, address `0x00` to `0x7F`, with a single read instruction
+    ///
+    /// The address of a byte in `out` is its index:
+    ///
+    /// | Address       | Registers                                                   |
+    /// |---------------|-------------------------------------------------------------|
+    /// | `0x00..=0x0B` | Filter 0 to 2, four id bytes each                           |
+    /// | `0x0C..=0x0D` | [`BFPCTRL`], `TXRTSCTRL`                                    |
+    /// | `0x10..=0x1B` | Filter 3 to 5                                               |
+    /// | `0x1C..=0x1D` | `TEC`, `REC`                                                |
+    /// | `0x20..=0x27` | Mask 0 and 1                                                |
+    /// | `0x28..=0x2D` | [`CNF3`], [`CNF2`], [`CNF1`], [`CANINTE`], [`CANINTF`], [`EFLG`] |
+    /// | `0x30..=0x3D` | Transmit buffer 0: control, id, DLC, 8 data bytes           |
+    /// | `0x40..=0x4D` | Transmit buffer 1                                           |
+    /// | `0x50..=0x5D` | Transmit buffer 2                                           |
+    /// | `0x60..=0x6D` | Receive buffer 0: control, id, DLC, 8 data bytes            |
+    /// | `0x70..=0x7D` | Receive buffer 1                                            |
+    ///
+    /// The last two addresses of every row, `0x_E` and `0x_F`, map to [`CANSTAT`] and [`CANCTRL`].
+    pub fn dump_registers(
+        &mut self,
+        out: &mut [u8; 0x80],
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.read_registers(0x00, out)
+    }
+
+    /// Read the control register of the selected transmit buffer
+    ///
+    /// All transmit buffer control registers share the layout of [`TXB0CTRL`].
+    pub fn read_tx_buffer_control(
+        &mut self,
+        buf_idx: TxBuffer,
+    ) -> Result<TXB0CTRL, <Self as SpiWithCs>::Error> {
+        let mut reg = [0];
+        self.read_registers(TXB0CTRL::ADDRESS + 0x10 * buf_idx as u8, &mut reg)?;
+        Ok(reg[0].into())
+    }
+
+    /// Read back the frame loaded into the selected transmit buffer
+    ///
+    /// The buffer keeps its content after the frame was sent.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::{MCP25xx, TxBuffer};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let loaded = mcp25xx.read_tx_buffer(TxBuffer::TXB0).unwrap();
+    /// ```
+    pub fn read_tx_buffer(
+        &mut self,
+        buf_idx: TxBuffer,
+    ) -> Result<CanFrame, <Self as SpiWithCs>::Error> {
+        let mut raw = [0; 13];
+        self.read_registers(TXB0CTRL::ADDRESS + 0x10 * buf_idx as u8 + 1, &mut raw)?;
+        let mut frame = CanFrame::from_raw(&raw);
+        frame.dlc.set_dlc(len_to_dlc(dlc_to_len(frame.dlc.dlc())));
+        Ok(frame)
+    }
+
+    /// Set the priorities of all three transmit buffers, leaving the other control bits untouched
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::{MCP25xx, TxPriority};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// // TXB2 is reserved for emergency frames
+    /// mcp25xx
+    ///     .set_tx_priorities(TxPriority::Low, TxPriority::Low, TxPriority::Highest)
+    ///     .unwrap();
+    /// ```
+    pub fn set_tx_priorities(
+        &mut self,
+        p0: TxPriority,
+        p1: TxPriority,
+        p2: TxPriority,
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        const TXP: u8 = 0b0000_0011;
+        self.modify_register(TXB0CTRL::new().with_txp(p0 as u8), TXP)?;
+        self.modify_register(TXB1CTRL::new().with_txp(p1 as u8), TXP)?;
+        self.modify_register(TXB2CTRL::new().with_txp(p2 as u8), TXP)
+    }
+
+    /// Request the selected transmit buffer to send a CAN frame
+    pub fn request_to_send(&mut self, buf_idx: TxBuffer) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.set_cs_low();
+        self.spi_write(&[Instruction::Rts as u8 | (1 << buf_idx as u8)])?;
+        self.set_cs_high();
+        Ok(())
+    }
+
+    /// Like [`Can::try_transmit`](embedded_can::Can::try_transmit), but returns the transmit buffer used
+    ///
+    /// Lets the caller check the completion of this particular frame with
+    /// [`MCP25xx::poll_transmit_complete`] or abort it later.
+    pub fn try_transmit_tracked(
+        &mut self,
+        frame: &CanFrame,
+    ) -> nb::Result<TxBuffer, <Self as SpiWithCs>::Error> {
+        let status = self.read_status()?;
+        let buf_idx = self.next_tx_buffer(status).ok_or(nb::Error::WouldBlock)?;
+
+        self.load_and_send(buf_idx, frame)?;
+        Ok(buf_idx)
+    }
+
+    /// Transmit a frame using only the listed transmit buffers
+    ///
+    /// The first free buffer in `allowed` is used, the round-robin setting does not apply.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// # use embedded_can::{Frame, StandardId};
+    /// use mcp25xx::{CanFrame, MCP25xx, TxBuffer};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    /// let frame = CanFrame::new(StandardId::new(0x100).unwrap(), &[1, 2, 3]).unwrap();
+    ///
+    /// let used = mcp25xx.try_transmit_on(&frame, &[TxBuffer::TXB0, TxBuffer::TXB2]).unwrap();
+    /// assert_eq!(used, TxBuffer::TXB0);
+    /// ```
+    pub fn try_transmit_on(
+        &mut self,
+        frame: &CanFrame,
+        allowed: &[TxBuffer],
+    ) -> nb::Result<TxBuffer, <Self as SpiWithCs>::Error> {
+        let status = self.read_status()?;
+        let buf_idx = allowed
+            .iter()
+            .copied()
+            .find(|&buf_idx| tx_buffer_free(status, buf_idx))
+            .ok_or(nb::Error::WouldBlock)?;
+
+        self.load_and_send(buf_idx, frame)?;
+        Ok(buf_idx)
+    }
+
+    /// Transmit a frame, replacing a pending frame of lower priority if all buffers are busy
+    ///
+    /// Returns the id of the aborted frame so the caller can queue it again.
+    /// A frame already being transmitted cannot be aborted, the call then returns [`nb::Error::WouldBlock`].
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use embedded_can::{Frame, StandardId};
+    /// use mcp25xx::{CanFrame, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let alarm = CanFrame::new(StandardId::ZERO, &[0xFF]).unwrap();
+    /// if let Ok(Some(aborted)) = mcp25xx.try_transmit_preemptive(&alarm) {
+    ///     // queue the frame with id `aborted` again
+    /// }
+    /// ```
+    pub fn try_transmit_preemptive(
+        &mut self,
+        frame: &CanFrame,
+    ) -> nb::Result<Option<Id>, <Self as SpiWithCs>::Error> {
+        let status = self.read_status()?;
+        if let Some(buf_idx) = self.next_tx_buffer(status) {
+            self.load_and_send(buf_idx, frame)?;
+            return Ok(None);
+        }
+
+        let mut victim: Option<(TxBuffer, u8, CanFrame)> = None;
+        for buf_idx in [TxBuffer::TXB0, TxBuffer::TXB1, TxBuffer::TXB2] {
+            // control register followed by id and DLC
+            let mut regs = [0; 6];
+            self.read_registers(TXB0CTRL::ADDRESS + 0x10 * buf_idx as u8, &mut regs)?;
+            let txp = TXB0CTRL::from(regs[0]).txp();
+            let mut raw = [0; 13];
+            raw[..5].copy_from_slice(&regs[1..]);
+            let pending = CanFrame::from_raw(&raw);
+            let sent_later = |(_, other_txp, other): &(TxBuffer, u8, CanFrame)| {
+                txp < *other_txp || (txp == *other_txp && pending.priority() > other.priority())
+            };
+            let replace = match &victim {
+                None => true,
+                Some(other) => sent_later(other),
+            };
+            if replace {
+                victim = Some((buf_idx, txp, pending));
+            }
+        }
+        let (buf_idx, _, pending) = victim.expect("three transmit buffers");
+        if frame.priority() >= pending.priority() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        self.abort_tx_buffer(buf_idx)?;
+        // a transmission in progress completes before TXREQ gets cleared
+        let ctrl = self.read_tx_buffer_control(buf_idx)?;
+        if ctrl.txreq() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        self.load_and_send(buf_idx, frame)?;
+        Ok(ctrl.abtf().then(|| pending.id()))
+    }
+
+    /// Check whether the frame requested with [`MCP25xx::request_to_send`] was sent
+    ///
+    /// Fails with [`Error::TransmitFailed`] if the request ended with a bus error or was aborted.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use embedded_can::{Frame, StandardId};
+    /// use mcp25xx::{CanFrame, MCP25xx, TxBuffer};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[1, 2, 3]).unwrap();
+    /// mcp25xx.load_tx_buffer(TxBuffer::TXB1, &frame).unwrap();
+    /// mcp25xx.request_to_send(TxBuffer::TXB1).unwrap();
+    /// nb::block!(mcp25xx.poll_transmit_complete(TxBuffer::TXB1)).unwrap();
+    /// ```
+    pub fn poll_transmit_complete(
+        &mut self,
+        buf_idx: TxBuffer,
+    ) -> nb::Result<(), Error<<Self as SpiWithCs>::Error>> {
+        let control = self.read_tx_buffer_control(buf_idx).map_err(Error::Spi)?;
+        self.track_arbitration_loss(buf_idx, control);
+        if control.txreq() {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.clear_interrupt(tx_interrupt(buf_idx))
+            .map_err(Error::Spi)?;
+        // MLOA stays set after a retransmission which won arbitration
+        if control.txerr() || control.abtf() {
+            return Err(nb::Error::Other(Error::TransmitFailed));
+        }
+        Ok(())
+    }
+
+    /// Whether the last transmit request of the selected buffer was acknowledged by another node
+    ///
+    /// Fails with [`Error::Busy`] while the request is pending without an error. The TXnIF flag is read,
+    /// so call this before anything clearing it, e.g. [`MCP25xx::poll_transmit_complete`].
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use embedded_can::{Frame, StandardId};
+    /// use mcp25xx::{CanFrame, MCP25xx, TxBuffer};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[]).unwrap();
+    /// mcp25xx.load_and_send(TxBuffer::TXB0, &frame).unwrap();
+    /// if !mcp25xx.last_tx_acked(TxBuffer::TXB0).unwrap() {
+    ///     // no other node on the bus, or wrong bitrate
+    /// }
+    /// ```
+    pub fn last_tx_acked(
+        &mut self,
+        buf_idx: TxBuffer,
+    ) -> Result<bool, Error<<Self as SpiWithCs>::Error>> {
+        let control = self.read_tx_buffer_control(buf_idx).map_err(Error::Spi)?;
+        if control.txerr() || control.abtf() {
+            return Ok(false);
+        }
+        if control.txreq() {
+            return Err(Error::Busy);
+        }
+        let flags: CANINTF = self.read_register().map_err(Error::Spi)?;
+        Ok(u8::from(flags) & tx_interrupt(buf_idx).mask() != 0)
+    }
+
+    /// Load a frame into the selected transmit buffer and request to send it right away
+    #[inline]
+    pub fn load_and_send(
+        &mut self,
+        buf_idx: TxBuffer,
+        frame: &CanFrame,
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.load_tx_buffer(buf_idx, frame)?;
+        self.request_to_send(buf_idx)
+    }
+
+    /// Setup the selected transmit buffer with CAN frame data
+    #[cfg(feature = "fast_instructions")]
+    pub fn load_tx_buffer(
+        &mut self,
+        buf_idx: TxBuffer,
+        frame: &CanFrame,
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        let data = frame.tx_bytes();
+
+        self.set_cs_low();
+        self.spi_write(&[Instruction::LoadTxBuffer as u8 | (buf_idx as u8 * 2)])?;
+        self.spi_write(data)?;
+        self.set_cs_high();
+        Ok(())
+    }
+
+    /// Setup the selected transmit buffer with CAN frame data
+    #[cfg(not(feature = "fast_instructions"))]
+    #[inline]
+    pub fn load_tx_buffer(
+        &mut self,
+        buf_idx: TxBuffer,
+        frame: &CanFrame,
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        let data = frame.tx_bytes();
+        self.write_registers(0x31 + 0x10 * buf_idx as u8, data)
+    }
+
+    /// Read CAN frame data from the selected receive buffer
+    pub fn read_rx_buffer(
+        &mut self,
+        buf_idx: RxBuffer,
+    ) -> Result<crate::frame::CanFrame, <Self as SpiWithCs>::Error> {
+        let mut frame = crate::frame::CanFrame::default();
+        self.read_rx_buffer_into(buf_idx, &mut frame)?;
+        Ok(frame)
+    }
+
+    /// Read CAN frame data from the selected receive buffer into an existing frame
+    ///
+    /// Data bytes beyond the received data length keep their previous content.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::{CanFrame, MCP25xx, RxBuffer};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let mut frame = CanFrame::default();
+    /// mcp25xx.read_rx_buffer_into(RxBuffer::RXB0, &mut frame).unwrap();
+    /// ```
+    pub fn read_rx_buffer_into(
+        &mut self,
+        buf_idx: RxBuffer,
+        frame: &mut crate::frame::CanFrame,
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        // gets a view into the first 5 bytes of Frame
+        fn id_bytes(frame: &mut crate::frame::CanFrame) -> &mut [u8; 5] {
+            // SAFETY:
+            // Frame is [repr(C)] without any padding bytes
+            // All bit patterns are valid
+            unsafe { &mut *(frame as *mut crate::frame::CanFrame as *mut [u8; 5]) }
+        }
+
+        self.set_cs_low();
+
+        self.send_read_rx_instruction(buf_idx)?;
+        let id_len = self.spi_transfer(id_bytes(frame))?.len();
+        self.last_raw_dlc = frame.dlc.dlc();
+        let dlc = dlc_to_len(self.last_raw_dlc);
+        frame.dlc.set_dlc(len_to_dlc(dlc));
+        let data_len = self.spi_transfer(&mut frame.data[0..dlc])?.len();
+        self.last_rx_truncated = id_len < 5 || data_len < dlc;
+        frame.take_rx_rtr();
+
+        self.set_cs_high();
+
+        #[cfg(not(feature = "fast_instructions"))]
+        // need to manually reset the interrupt flag bit if Instruction::ReadRxBuffer is not available.
+        // A frame may arrive in the other buffer at any time until here, so the mask
+        // must only cover this buffer's flag. The controller applies BitModify atomically,
+        // which keeps a flag set in the meantime.
+        self.modify_register(CANINTF::new(), 1 << buf_idx as u8)?;
+        Ok(())
+    }
+
+    /// Read the selected receive buffer without decoding it
+    ///
+    /// Returns the number of bytes written to `buf`, 5 register bytes plus the data length.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::{MCP25xx, RxBuffer};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let mut buf = [0; 13];
+    /// let len = mcp25xx.read_rx_buffer_raw(RxBuffer::RXB0, &mut buf).unwrap();
+    /// let (header, data) = buf[..len].split_at(5);
+    /// # assert_eq!(header.len(), 5);
+    /// # assert!(data.is_empty());
+    /// ```
+    pub fn read_rx_buffer_raw(
+        &mut self,
+        buf_idx: RxBuffer,
+        buf: &mut [u8; 13],
+    ) -> Result<usize, <Self as SpiWithCs>::Error> {
+        self.set_cs_low();
+
+        self.send_read_rx_instruction(buf_idx)?;
+        self.spi_transfer(&mut buf[0..5])?;
+        let len = 5 + dlc_to_len(DLC::from(buf[4]).dlc());
+        self.spi_transfer(&mut buf[5..len])?;
+
+        self.set_cs_high();
+
+        #[cfg(not(feature = "fast_instructions"))]
+        // need to manually reset the interrupt flag bit if Instruction::ReadRxBuffer is not available
+        self.modify_register(CANINTF::new(), 1 << buf_idx as u8)?;
+        Ok(len)
+    }
+
+    /// Read [`CANINTF`] and return the pending interrupt with the highest priority
+    ///
+    /// The interrupt flag is not cleared, see [`MCP25xx::clear_interrupt`].
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::{InterruptSource, MCP25xx, RxBuffer};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    /// let mut dropped_frames = 0;
+    ///
+    /// while let Some(source) = mcp25xx.next_interrupt().unwrap() {
+    ///     match source {
+    ///         InterruptSource::RXB0 => {
+    ///             // reading the buffer clears the flag
+    ///             let _frame = mcp25xx.read_rx_buffer(RxBuffer::RXB0).unwrap();
+    ///             continue;
+    ///         }
+    ///         InterruptSource::Error => {
+    ///             let (rx0ovr, rx1ovr) = mcp25xx.check_rx_overflow().unwrap();
+    ///             dropped_frames += rx0ovr as u32 + rx1ovr as u32;
+    ///             mcp25xx.clear_rx_overflow().unwrap();
+    ///         }
+    ///         _ => { /* handle the others */ }
+    ///     }
+    ///     mcp25xx.clear_interrupt(source).unwrap();
+    /// }
+    /// ```
+    pub fn next_interrupt(
+        &mut self,
+    ) -> Result<Option<InterruptSource>, <Self as SpiWithCs>::Error> {
+        let flags: u8 = self.read_register::<CANINTF>()?.into();
+        Ok(InterruptSource::PRIORITY
+            .into_iter()
+            .find(|source| flags & source.mask() != 0))
+    }
+
+    /// Find out why the controller left Sleep mode and clear the wake-up flag
+    ///
+    /// The operation mode is left to the caller.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::registers::OperationMode;
+    /// use mcp25xx::{MCP25xx, WakeReason};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// if mcp25xx.on_wake().unwrap() == WakeReason::BusActivity {
+    ///     mcp25xx.set_mode(OperationMode::NormalOperation).unwrap();
+    /// }
+    /// ```
+    pub fn on_wake(&mut self) -> Result<WakeReason, <Self as SpiWithCs>::Error> {
+        let canintf: CANINTF = self.read_register()?;
+        if !canintf.wakif() {
+            return Ok(WakeReason::Command);
+        }
+        self.clear_interrupt(InterruptSource::WakeUp)?;
+        Ok(WakeReason::BusActivity)
+    }
+
+    /// Clear the flag of a single interrupt source
+    pub fn clear_interrupt(
+        &mut self,
+        source: InterruptSource,
+    ) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.modify_register(CANINTF::new(), source.mask())
+    }
+
+    /// Handle all pending interrupts, meant as the body of the interrupt handler of the INT pin
+    ///
+    /// Passes an [`Event`] for each flag set in [`CANINTF`] to `f`, received frames first.
+    /// Flags set while `f` runs are kept, so an edge triggered interrupt may need another call.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::{Event, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// mcp25xx
+    ///     .on_interrupt(|event| match event {
+    ///         Event::Received(_frame) => { /* queue the frame for the application */ }
+    ///         Event::TransmitComplete(_buf_idx) => { /* load the next frame */ }
+    ///         Event::Error(eflg) if eflg.txbo() => { /* bus-off */ }
+    ///         _ => {}
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn on_interrupt<F>(&mut self, mut f: F) -> Result<(), <Self as SpiWithCs>::Error>
+    where
+        F: FnMut(Event),
+    {
+        let flags = self.read_register::<CANINTF>()?;
+        if flags.rx0if() {
+            f(Event::Received(self.read_rx_buffer(RxBuffer::RXB0)?));
+        }
+        if flags.rx1if() {
+            f(Event::Received(self.read_rx_buffer(RxBuffer::RXB1)?));
+        }
+
+        let mut handled = 0;
+        for (set, buf_idx) in [
+            (flags.tx0if(), TxBuffer::TXB0),
+            (flags.tx1if(), TxBuffer::TXB1),
+            (flags.tx2if(), TxBuffer::TXB2),
+        ] {
+            if set {
+                f(Event::TransmitComplete(buf_idx));
+                handled |= tx_interrupt(buf_idx).mask();
+            }
+        }
+        if flags.errif() {
+            let eflg: EFLG = self.read_register()?;
+            f(Event::Error(eflg));
+            if eflg.rx0ovr() || eflg.rx1ovr() {
+                self.clear_rx_overflow()?;
+            }
+            handled |= InterruptSource::Error.mask();
+        }
+        if flags.wakif() {
+            f(Event::WakeUp);
+            handled |= InterruptSource::WakeUp.mask();
+        }
+        if flags.merrf() {
+            f(Event::MessageError);
+            handled |= InterruptSource::MessageError.mask();
+        }
+        if handled != 0 {
+            self.modify_register(CANINTF::new(), handled)?;
+        }
+        Ok(())
+    }
 
-impl<SPI, CS> embedded_can::blocking::Default for MCP25xx<SPI, CS> where Self: SpiWithCs {}
+    /// Read the `icod` field of [`CANSTAT`]
+    ///
+    /// Faster alternative to [`MCP25xx::next_interrupt`] which does not report message errors.
+    /// Only the interrupts enabled in [`CANINTE`] are reported.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::{InterruptAction, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// while let Some(source) = mcp25xx.interrupt_code().unwrap().source() {
+    ///     if let InterruptAction::ReadRxBuffer(buf_idx) = source.action() {
+    ///         let _frame = mcp25xx.read_rx_buffer(buf_idx).unwrap();
+    ///     }
+    ///     mcp25xx.clear_interrupt(source).unwrap();
+    /// }
+    /// ```
+    pub fn interrupt_code(&mut self) -> Result<InterruptFlagCode, <Self as SpiWithCs>::Error> {
+        Ok(self.read_register::<CANSTAT>()?.icod())
+    }
 
-impl<SPI, CS> MCP25xx<SPI, CS>
-where
-    Self: SpiWithCs,
-{
-    /// Read a single register
-    pub fn read_register<R: Register>(&mut self) -> Result<R, <Self as SpiWithCs>::Error> {
-        self.set_cs_low();
-        self.spi_write(&[Instruction::Read as u8, R::ADDRESS])?;
-        let mut reg = [0];
-        self.spi_transfer(&mut reg)?;
-        self.set_cs_high();
-        Ok(reg[0].into())
+    /// Read the receive buffer overflow flags of [`EFLG`] for RXB0 and RXB1
+    ///
+    /// The flags stay set until cleared with [`MCP25xx::clear_rx_overflow`].
+    pub fn check_rx_overflow(&mut self) -> Result<(bool, bool), <Self as SpiWithCs>::Error> {
+        let eflg: EFLG = self.read_register()?;
+        Ok((eflg.rx0ovr(), eflg.rx1ovr()))
     }
 
-    /// Write a single register
-    pub fn write_register<R: Register + Into<u8>>(
-        &mut self,
-        reg: R,
-    ) -> Result<(), <Self as SpiWithCs>::Error> {
-        self.set_cs_low();
-        self.spi_write(&[Instruction::Write as u8, R::ADDRESS, reg.into()])?;
-        self.set_cs_high();
-        Ok(())
+    /// Clear both receive buffer overflow flags, leaving the other bits of [`EFLG`] untouched
+    pub fn clear_rx_overflow(&mut self) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.modify_register(EFLG::new(), 0b1100_0000)
     }
 
-    /// Modify a single register
+    /// Read [`EFLG`] and report a change of the error state since the last call
     ///
-    /// Only registers implementing [`Modify`] support the `Modify` Instruction
-    pub fn modify_register<R: Register + Modify + Into<u8>>(
-        &mut self,
-        reg: R,
-        mask: u8,
-    ) -> Result<(), <Self as SpiWithCs>::Error> {
-        self.set_cs_low();
-        self.spi_write(&[Instruction::BitModify as u8, R::ADDRESS, mask, reg.into()])?;
-        self.set_cs_high();
-        Ok(())
+    /// The driver starts out assuming [`ErrorState::Active`].
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::{ErrorState, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// if let Some(event) = mcp25xx.poll_error_state().unwrap() {
+    ///     if event.current == ErrorState::BusOff {
+    ///         // reinitialize the controller
+    ///     }
+    /// }
+    /// ```
+    pub fn poll_error_state(&mut self) -> Result<Option<ErrorEvent>, <Self as SpiWithCs>::Error> {
+        let current = ErrorState::from(self.read_register::<EFLG>()?);
+        let previous = core::mem::replace(&mut self.error_state, current);
+        Ok((previous != current).then_some(ErrorEvent { previous, current }))
     }
 
-    /// Read multiple consecutive registers
-    pub fn read_registers(
+    /// Clear the error flags after an error condition and return to operation `mode`
+    ///
+    /// The error counters are not reset, the controller decrements them on its own.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::registers::OperationMode;
+    /// use mcp25xx::{ErrorState, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// if let Some(event) = mcp25xx.poll_error_state().unwrap() {
+    ///     if event.previous == ErrorState::BusOff {
+    ///         mcp25xx.recover(OperationMode::NormalOperation).unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn recover(
         &mut self,
-        start_address: u8,
-        buf: &mut [u8],
-    ) -> Result<(), <Self as SpiWithCs>::Error> {
-        self.set_cs_low();
-        self.spi_write(&[Instruction::Read as u8, start_address])?;
-        self.spi_transfer(buf)?;
-        self.set_cs_high();
+        mode: OperationMode,
+    ) -> Result<(), Error<<Self as SpiWithCs>::Error>> {
+        self.clear_rx_overflow().map_err(Error::Spi)?;
+        self.modify_register(
+            CANINTF::new(),
+            InterruptSource::Error.mask() | InterruptSource::MessageError.mask(),
+        )
+        .map_err(Error::Spi)?;
+        self.set_mode(mode).map_err(Error::Spi)?;
+        self.wait_for_mode(mode)?;
+        self.error_state = ErrorState::Active;
         Ok(())
     }
 
-    /// Write multiple consecutive registers
-    pub fn write_registers(
+    /// Configure the RXnBF pin of a receive buffer, see [`RxBfPinMode`]
+    pub fn rx_bf_pin_mode(
         &mut self,
-        start_address: u8,
-        data: &[u8],
+        pin: RxBuffer,
+        mode: RxBfPinMode,
     ) -> Result<(), <Self as SpiWithCs>::Error> {
-        self.set_cs_low();
-        self.spi_write(&[Instruction::Write as u8, start_address])?;
-        self.spi_write(data)?;
-        self.set_cs_high();
-        Ok(())
-    }
-
-    /// Request the selected transmit buffer to send a CAN frame
-    pub fn request_to_send(&mut self, buf_idx: TxBuffer) -> Result<(), <Self as SpiWithCs>::Error> {
-        self.set_cs_low();
-        self.spi_write(&[Instruction::Rts as u8 | (1 << buf_idx as u8)])?;
-        self.set_cs_high();
-        Ok(())
+        let enabled = mode != RxBfPinMode::Disabled;
+        let interrupt = mode == RxBfPinMode::Interrupt;
+        let (reg, mask) = match pin {
+            RxBuffer::RXB0 => (
+                BFPCTRL::new().with_b0bfe(enabled).with_b0bfm(interrupt),
+                0b0000_0101,
+            ),
+            RxBuffer::RXB1 => (
+                BFPCTRL::new().with_b1bfe(enabled).with_b1bfm(interrupt),
+                0b0000_1010,
+            ),
+        };
+        self.modify_register(reg, mask)
     }
 
-    /// Setup the selected transmit buffer with CAN frame data
-    #[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
-    pub fn load_tx_buffer(
+    /// Drive the RXnBF pin of a receive buffer high or low
+    ///
+    /// Only has an effect in [`RxBfPinMode::DigitalOutput`] mode.
+    pub fn set_rx_bf_pin(
         &mut self,
-        buf_idx: TxBuffer,
-        frame: &CanFrame,
+        pin: RxBuffer,
+        state: bool,
     ) -> Result<(), <Self as SpiWithCs>::Error> {
-        let data = &frame.as_bytes()[0..5 + frame.dlc()];
-
-        self.set_cs_low();
-        self.spi_write(&[Instruction::LoadTxBuffer as u8 | (buf_idx as u8 * 2)])?;
-        self.spi_write(data)?;
-        self.set_cs_high();
-        Ok(())
+        let (reg, mask) = match pin {
+            RxBuffer::RXB0 => (BFPCTRL::new().with_b0bfs(state), 0b0001_0000),
+            RxBuffer::RXB1 => (BFPCTRL::new().with_b1bfs(state), 0b0010_0000),
+        };
+        self.modify_register(reg, mask)
     }
 
-    /// Setup the selected transmit buffer with CAN frame data
-    #[cfg(not(any(feature = "mcp2515", feature = "mcp25625")))]
-    #[inline]
-    pub fn load_tx_buffer(
+    /// Read back mode and output state of the RXnBF pin of a receive buffer
+    pub fn rx_bf_pin(
         &mut self,
-        buf_idx: TxBuffer,
-        frame: &CanFrame,
-    ) -> Result<(), <Self as SpiWithCs>::Error> {
-        let data = &frame.as_bytes()[0..5 + frame.dlc()];
-        self.write_registers(0x31 + 0x10 * buf_idx as u8, data)
+        pin: RxBuffer,
+    ) -> Result<(RxBfPinMode, bool), <Self as SpiWithCs>::Error> {
+        let bfpctrl: BFPCTRL = self.read_register()?;
+        let (enabled, interrupt, state) = match pin {
+            RxBuffer::RXB0 => (bfpctrl.b0bfe(), bfpctrl.b0bfm(), bfpctrl.b0bfs()),
+            RxBuffer::RXB1 => (bfpctrl.b1bfe(), bfpctrl.b1bfm(), bfpctrl.b1bfs()),
+        };
+        let mode = match (enabled, interrupt) {
+            (false, _) => RxBfPinMode::Disabled,
+            (true, false) => RxBfPinMode::DigitalOutput,
+            (true, true) => RxBfPinMode::Interrupt,
+        };
+        Ok((mode, state))
     }
 
-    /// Read CAN frame data from the selected receive buffer
-    pub fn read_rx_buffer(
+    /// Read the levels of the TX0RTS, TX1RTS and TX2RTS pins
+    ///
+    /// Only pins configured as digital inputs with [`ConfigMode::set_tx_rts_pins`] report their level,
+    /// pins requesting transmissions always read as `false`.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::MCP25xx;
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// // TX1RTS stays a transmit request, the other two pins are spare inputs
+    /// let mut config_mode = mcp25xx.enter_config_mode().unwrap();
+    /// config_mode.set_tx_rts_pins([false, true, false]).unwrap();
+    /// drop(config_mode);
+    ///
+    /// let [tx0rts, _, tx2rts] = mcp25xx.read_tx_rts_inputs().unwrap();
+    /// # assert!(!tx0rts && !tx2rts);
+    /// ```
+    pub fn read_tx_rts_inputs(&mut self) -> Result<[bool; 3], <Self as SpiWithCs>::Error> {
+        let txrtsctrl: TXRTSCTRL = self.read_register()?;
+        Ok([txrtsctrl.b0rts(), txrtsctrl.b1rts(), txrtsctrl.b2rts()])
+    }
+
+    /// Receive the next frame accepted by `pred`, discarding the others
+    ///
+    /// Returns [`nb::Error::WouldBlock`] if none of the frames already received is accepted.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use embedded_can::Frame;
+    /// use mcp25xx::MCP25xx;
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// // only frames with at least two data bytes
+    /// match mcp25xx.receive_filtered(|frame| frame.dlc() >= 2) {
+    ///     Ok(_frame) => {}
+    ///     Err(nb::Error::WouldBlock) => {}
+    ///     Err(nb::Error::Other(e)) => panic!("{:?}", e),
+    /// }
+    /// ```
+    pub fn receive_filtered<F>(
         &mut self,
-        buf_idx: RxBuffer,
-    ) -> Result<crate::frame::CanFrame, <Self as SpiWithCs>::Error> {
-        // gets a view into the first 5 bytes of Frame
-        fn id_bytes(frame: &mut crate::frame::CanFrame) -> &mut [u8; 5] {
-            // SAFETY:
-            // Frame is [repr(C)] without any padding bytes
-            // All bit patterns are valid
-            unsafe { &mut *(frame as *mut crate::frame::CanFrame as *mut [u8; 5]) }
+        mut pred: F,
+    ) -> nb::Result<CanFrame, <Self as SpiWithCs>::Error>
+    where
+        F: FnMut(&CanFrame) -> bool,
+    {
+        let status = self.read_status()?;
+        for (buf_idx, full) in [
+            (RxBuffer::RXB0, status.rx0if()),
+            (RxBuffer::RXB1, status.rx1if()),
+        ] {
+            if !full {
+                continue;
+            }
+            let frame = self.read_rx_buffer(buf_idx)?;
+            if pred(&frame) {
+                return Ok(frame);
+            }
         }
+        Err(nb::Error::WouldBlock)
+    }
 
-        let mut frame = crate::frame::CanFrame::default();
-
-        self.set_cs_low();
+    /// Receive the next frame passing `filters`, discarding the others
+    ///
+    /// Same as [`MCP25xx::receive_filtered`] with [`SoftwareFilterSet::matches`] as predicate.
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use embedded_can::StandardId;
+    /// use mcp25xx::{MCP25xx, SoftwareFilterSet};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// let mut filters: SoftwareFilterSet<32> = SoftwareFilterSet::new();
+    /// for id in [0x081, 0x1A4, 0x3F0] {
+    ///     filters.add(StandardId::new(id).unwrap(), 0x7FF).unwrap();
+    /// }
+    /// mcp25xx.set_promiscuous(true).unwrap();
+    /// if let Ok(frame) = mcp25xx.receive_sw_filtered(&filters) {
+    ///     // frame has one of the three ids
+    /// }
+    /// ```
+    pub fn receive_sw_filtered<const N: usize>(
+        &mut self,
+        filters: &SoftwareFilterSet<N>,
+    ) -> nb::Result<CanFrame, <Self as SpiWithCs>::Error> {
+        self.receive_filtered(|frame| filters.matches(frame.id()))
+    }
 
-        self.send_read_rx_instruction(buf_idx)?;
-        self.spi_transfer(id_bytes(&mut frame))?;
-        let mut dlc = frame.dlc();
-        if dlc > 8 {
-            dlc = 8;
-            frame.dlc.set_dlc(8);
-        }
-        self.spi_transfer(&mut frame.data[0..dlc])?;
+    /// Receive all frames on the bus regardless of filters and masks
+    ///
+    /// Disabling restores the previous `rxm` fields of both receive buffers.
+    pub fn set_promiscuous(&mut self, enabled: bool) -> Result<(), <Self as SpiWithCs>::Error> {
+        const RXM_MASK: u8 = 0b0110_0000;
 
-        self.set_cs_high();
+        let (rxm0, rxm1) = if enabled {
+            if self.saved_rxm.is_some() {
+                return Ok(());
+            }
+            let rxb0ctrl: RXB0CTRL = self.read_register()?;
+            let rxb1ctrl: RXB1CTRL = self.read_register()?;
+            self.saved_rxm = Some((rxb0ctrl.rxm(), rxb1ctrl.rxm()));
+            (RXM::ReceiveAny, RXM::ReceiveAny)
+        } else {
+            match self.saved_rxm.take() {
+                Some(saved) => saved,
+                None => return Ok(()),
+            }
+        };
+        self.modify_register(RXB0CTRL::new().with_rxm(rxm0), RXM_MASK)?;
+        self.modify_register(RXB1CTRL::new().with_rxm(rxm1), RXM_MASK)
+    }
 
-        #[cfg(not(any(feature = "mcp2515", feature = "mcp25625")))]
-        // need to manually reset the interrupt flag bit if Instruction::ReadRxBuffer is not available
-        self.modify_register(CANINTF::new(), 1 << buf_idx as u8)?;
-        Ok(frame)
+    /// Discard the frames held by both receive buffers
+    ///
+    /// Clears the `rx0if` and `rx1if` flags of [`CANINTF`] without reading the buffers,
+    /// which releases them for new frames. The content of the buffers is lost.
+    pub fn flush_rx(&mut self) -> Result<(), <Self as SpiWithCs>::Error> {
+        self.modify_register(CANINTF::new(), 0b0000_0011)
     }
 
-    #[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
+    #[cfg(feature = "fast_instructions")]
     fn send_read_rx_instruction(
         &mut self,
         buf_idx: RxBuffer,
@@ -387,7 +2456,7 @@ where
         self.spi_write(&[Instruction::ReadRxBuffer as u8 | (buf_idx as u8 * 2)])
     }
 
-    #[cfg(not(any(feature = "mcp2515", feature = "mcp25625")))]
+    #[cfg(not(feature = "fast_instructions"))]
     fn send_read_rx_instruction(
         &mut self,
         buf_idx: RxBuffer,
@@ -396,8 +2465,84 @@ where
     }
 }
 
+/// Time in microseconds to wait after power-up before the controller is accessed
+///
+/// The controller is held in reset until its oscillator ran for 128 cycles.
+/// Most of this time is needed for a crystal to start oscillating, which usually takes a few milliseconds.
+pub const OSC_STARTUP_US: u32 = 5_000;
+
+/// Time in microseconds to wait after a reset instruction before the controller is accessed
+///
+/// After a reset the controller waits another 128 oscillator cycles before it accepts commands.
+/// This covers oscillators down to 1 MHz.
+pub const RESET_DELAY_US: u32 = 128;
+
+/// Time in microseconds between two polls of [`MCP25xx::receive_with_timeout`]
+pub const RECEIVE_POLL_INTERVAL_US: u32 = 100;
+
+/// Time in microseconds between two polls of the transmit functions, e.g. [`MCP25xx::send_blocking`]
+pub const TRANSMIT_POLL_INTERVAL_US: u32 = 10;
+
+/// Default number of CANSTAT reads before a requested mode change is considered failed
+const MODE_CHANGE_POLLS: usize = 100;
+
+/// Interrupt raised when the transmit request of a buffer completes
+fn tx_interrupt(buf_idx: TxBuffer) -> InterruptSource {
+    match buf_idx {
+        TxBuffer::TXB0 => InterruptSource::TXB0,
+        TxBuffer::TXB1 => InterruptSource::TXB1,
+        TxBuffer::TXB2 => InterruptSource::TXB2,
+    }
+}
+
+/// Wait one poll interval, shortened to the remaining time
+///
+/// Returns `false` without waiting once `timeout_us` passed.
+fn poll_delay(
+    delay: &mut impl DelayUs<u32>,
+    interval: u32,
+    waited: &mut u32,
+    timeout_us: u32,
+) -> bool {
+    if *waited >= timeout_us {
+        return false;
+    }
+    let interval = core::cmp::min(interval, timeout_us - *waited);
+    delay.delay_us(interval);
+    *waited += interval;
+    true
+}
+
+/// Splits consecutive filter or mask registers into their headers
+fn headers<const B: usize, const N: usize>(bytes: [u8; B]) -> [IdHeader; N] {
+    core::array::from_fn(|i| {
+        let mut header = [0; 4];
+        header.copy_from_slice(&bytes[4 * i..4 * i + 4]);
+        IdHeader::from_bytes(header)
+    })
+}
+
+/// Returns the first transmit buffer without a pending transmit request,
+/// starting the search at buffer `start`
+fn free_tx_buffer(status: ReadStatusResponse, start: u8) -> Option<TxBuffer> {
+    const BUFFERS: [TxBuffer; 3] = [TxBuffer::TXB0, TxBuffer::TXB1, TxBuffer::TXB2];
+    (0..3)
+        .map(|i| BUFFERS[((start + i) % 3) as usize])
+        .find(|&buf_idx| tx_buffer_free(status, buf_idx))
+}
+
+fn tx_buffer_free(status: ReadStatusResponse, buf_idx: TxBuffer) -> bool {
+    match buf_idx {
+        TxBuffer::TXB0 => !status.txreq0(),
+        TxBuffer::TXB1 => !status.txreq1(),
+        TxBuffer::TXB2 => !status.txreq2(),
+    }
+}
+
 /// Filters and Masks of the two receive buffers
-#[derive(Copy, Clone, Debug)]
+///
+/// The assignment to the receive buffers is fixed, see [`AcceptanceFilter::receive_buffer`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum AcceptanceFilter {
     /// Associated with Receive Buffer 0
     Filter0 = 0x00,
@@ -417,8 +2562,36 @@ pub enum AcceptanceFilter {
     Mask1 = 0x24,
 }
 
+impl AcceptanceFilter {
+    /// Receive buffer the filter or mask applies to
+    ///
+    /// | Receive buffer | Mask    | Filters                        |
+    /// |----------------|---------|--------------------------------|
+    /// | RXB0           | `Mask0` | `Filter0`, `Filter1`           |
+    /// | RXB1           | `Mask1` | `Filter2` to `Filter5`         |
+    pub fn receive_buffer(self) -> RxBuffer {
+        use AcceptanceFilter::*;
+        match self {
+            Filter0 | Filter1 | Mask0 => RxBuffer::RXB0,
+            Filter2 | Filter3 | Filter4 | Filter5 | Mask1 => RxBuffer::RXB1,
+        }
+    }
+
+    /// Position in the filter cache
+    fn index(self) -> usize {
+        // addresses 0x00 to 0x24 in steps of 4, skipping 0x0C to 0x0F and 0x1C to 0x1F
+        let address = self as usize;
+        address / 4 - address / 16
+    }
+
+    /// Whether this is one of the two masks
+    pub fn is_mask(self) -> bool {
+        matches!(self, AcceptanceFilter::Mask0 | AcceptanceFilter::Mask1)
+    }
+}
+
 /// Transmit buffer
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TxBuffer {
     /// Transmit buffer 0
     TXB0 = 0,
@@ -428,8 +2601,35 @@ pub enum TxBuffer {
     TXB2 = 2,
 }
 
+/// Result of [`MCP25xx::hot_swap_filters`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct HotSwapReport {
+    /// Number of filters and masks written
+    pub written: u8,
+    /// Bytes transferred over SPI while the node was off the bus, zero if nothing changed
+    pub off_bus_bytes: u32,
+    /// Microseconds waited for the controller to confirm the previous operation mode
+    pub mode_wait_us: u32,
+}
+
+/// Transmit buffer priority, the TXP bits of [`TXB0CTRL`]
+///
+/// Among buffers with pending transmit requests the one with the highest priority is sent first,
+/// ties go to the higher buffer number.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TxPriority {
+    /// Lowest message priority
+    Lowest = 0b00,
+    /// Low intermediate message priority
+    Low = 0b01,
+    /// High intermediate message priority
+    High = 0b10,
+    /// Highest message priority
+    Highest = 0b11,
+}
+
 /// Receive buffer
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum RxBuffer {
     /// Receive Buffer 0
     RXB0 = 0,
@@ -437,6 +2637,239 @@ pub enum RxBuffer {
     RXB1 = 1,
 }
 
+/// Frames waiting in the receive buffers, see [`MCP25xx::pending_rx`]
+#[cfg(feature = "fast_instructions")]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+#[derive(Copy, Clone, Debug)]
+pub struct PendingRx(pub RxStatusResponse);
+
+#[cfg(feature = "fast_instructions")]
+impl PendingRx {
+    /// Whether the receive buffer holds a frame
+    pub fn contains(&self, buf: RxBuffer) -> bool {
+        match buf {
+            RxBuffer::RXB0 => self.0.rx0if(),
+            RxBuffer::RXB1 => self.0.rx1if(),
+        }
+    }
+
+    /// Number of frames waiting, up to 2
+    pub fn count(&self) -> u8 {
+        self.0.rx0if() as u8 + self.0.rx1if() as u8
+    }
+
+    /// Type of the frame in the receive buffer, if known
+    ///
+    /// The status only describes one frame, the buffer it is in follows from the matching filter.
+    /// With both buffers full, the type of the other frame is `None`.
+    pub fn message_type(&self, buf: RxBuffer) -> Option<MessageType> {
+        let described = match self.0.filter_match() {
+            FilterMatch::RXF0 | FilterMatch::RXF1 => RxBuffer::RXB0,
+            _ => RxBuffer::RXB1,
+        };
+        (self.contains(buf) && buf == described).then(|| self.0.message_type())
+    }
+}
+
+/// Function of a RXnBF pin, configured in [`BFPCTRL`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RxBfPinMode {
+    /// Pin is high impedance
+    Disabled,
+    /// Pin goes low when a valid message is loaded into the receive buffer
+    Interrupt,
+    /// Pin is driven by [`MCP25xx::set_rx_bf_pin`]
+    DigitalOutput,
+}
+
+/// Source of an interrupt, numbered by its flag bit in [`CANINTF`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InterruptSource {
+    /// Receive Buffer 0 Full
+    RXB0 = 0,
+    /// Receive Buffer 1 Full
+    RXB1 = 1,
+    /// Transmit Buffer 0 Empty
+    TXB0 = 2,
+    /// Transmit Buffer 1 Empty
+    TXB1 = 3,
+    /// Transmit Buffer 2 Empty
+    TXB2 = 4,
+    /// Error (multiple sources in the [`EFLG`] register)
+    Error = 5,
+    /// Wake-up
+    WakeUp = 6,
+    /// Message Error
+    MessageError = 7,
+}
+
+impl InterruptSource {
+    /// Sources ordered from highest to lowest priority
+    ///
+    /// Follows the priority of the `icod` field of [`CANSTAT`].
+    /// Message errors are not covered by `icod` and come last.
+    pub const PRIORITY: [InterruptSource; 8] = [
+        InterruptSource::Error,
+        InterruptSource::WakeUp,
+        InterruptSource::TXB0,
+        InterruptSource::TXB1,
+        InterruptSource::TXB2,
+        InterruptSource::RXB0,
+        InterruptSource::RXB1,
+        InterruptSource::MessageError,
+    ];
+
+    /// Bit of this source in the [`CANINTF`] and [`CANINTE`] registers
+    #[inline]
+    pub const fn mask(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// Recommended handling of this interrupt
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::{InterruptAction, MCP25xx};
+    ///
+    /// let mut mcp25xx: MCP25xx<_, _> = get_mcp25xx();
+    ///
+    /// while let Some(source) = mcp25xx.next_interrupt().unwrap() {
+    ///     match source.action() {
+    ///         InterruptAction::ReadRxBuffer(buf_idx) => {
+    ///             let _frame = mcp25xx.read_rx_buffer(buf_idx).unwrap();
+    ///             continue;
+    ///         }
+    ///         InterruptAction::CheckTransmitResult(buf_idx) => {
+    ///             let _ctrl = mcp25xx.read_tx_buffer_control(buf_idx).unwrap();
+    ///         }
+    ///         InterruptAction::HandleError => {
+    ///             let _event = mcp25xx.poll_error_state().unwrap();
+    ///         }
+    ///         InterruptAction::HandleWakeUp | InterruptAction::HandleMessageError => {}
+    ///     }
+    ///     mcp25xx.clear_interrupt(source).unwrap();
+    /// }
+    /// ```
+    pub const fn action(self) -> InterruptAction {
+        match self {
+            InterruptSource::RXB0 => InterruptAction::ReadRxBuffer(RxBuffer::RXB0),
+            InterruptSource::RXB1 => InterruptAction::ReadRxBuffer(RxBuffer::RXB1),
+            InterruptSource::TXB0 => InterruptAction::CheckTransmitResult(TxBuffer::TXB0),
+            InterruptSource::TXB1 => InterruptAction::CheckTransmitResult(TxBuffer::TXB1),
+            InterruptSource::TXB2 => InterruptAction::CheckTransmitResult(TxBuffer::TXB2),
+            InterruptSource::Error => InterruptAction::HandleError,
+            InterruptSource::WakeUp => InterruptAction::HandleWakeUp,
+            InterruptSource::MessageError => InterruptAction::HandleMessageError,
+        }
+    }
+}
+
+/// Handling of an interrupt source, see [`InterruptSource::action`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InterruptAction {
+    /// Read the frame with [`MCP25xx::read_rx_buffer`], which clears the flag
+    /// with the `mcp2515` or `mcp25625` feature
+    ReadRxBuffer(RxBuffer),
+    /// The transmit request ended, check [`MCP25xx::read_tx_buffer_control`] for errors
+    /// and load the next frame
+    CheckTransmitResult(TxBuffer),
+    /// Read [`EFLG`], e.g. with [`MCP25xx::poll_error_state`] or [`MCP25xx::check_rx_overflow`],
+    /// and clear the overflow flags
+    HandleError,
+    /// The controller left Sleep mode because of bus activity and is in Listen-Only mode,
+    /// request the operation mode with [`MCP25xx::set_mode`]
+    HandleWakeUp,
+    /// A frame was received or transmitted with an error, the error counters tell more
+    HandleMessageError,
+}
+
+/// Interrupt handled by [`MCP25xx::on_interrupt`]
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// A frame was read from a receive buffer
+    Received(CanFrame),
+    /// The transmit request of the buffer ended, check [`MCP25xx::read_tx_buffer_control`]
+    /// for errors and load the next frame
+    TransmitComplete(TxBuffer),
+    /// The error flags changed, see [`ErrorState::from`] for the fault confinement state
+    Error(EFLG),
+    /// The controller left Sleep mode because of bus activity and is in Listen-Only mode
+    WakeUp,
+    /// A frame was received or transmitted with an error
+    MessageError,
+}
+
+/// Outcome of [`MCP25xx::one_shot_send`]
+#[cfg(feature = "fast_instructions")]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OneShotResult {
+    /// The frame was transmitted and acknowledged
+    Sent,
+    /// A frame with a higher priority won arbitration, this one was not sent
+    ArbitrationLost,
+    /// The attempt ran into a bus error, e.g. no acknowledgement, or was aborted
+    Error,
+    /// No transmit buffer got free or the attempt did not end in time, the request was aborted
+    Timeout,
+}
+
+/// Features of the chip the driver is built for, see [`MCP25xx::capabilities`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// READ RX BUFFER, LOAD TX BUFFER and RX STATUS instructions,
+    /// enabled by the `mcp2515` or `mcp25625` feature
+    pub fast_instructions: bool,
+    /// CAN transceiver in the same package, enabled by the `mcp25625` feature
+    pub integrated_transceiver: bool,
+    /// Transceiver settings reachable through SPI, never the case: the standby pin of the
+    /// MCP25625 has to be driven by a GPIO, its slope control is fixed
+    pub transceiver_control: bool,
+}
+
+/// Fault confinement state of the controller
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ErrorState {
+    /// Error counters are below 96
+    Active,
+    /// An error counter reached 96
+    Warning,
+    /// An error counter reached 128
+    Passive,
+    /// The transmit error counter exceeded 255
+    BusOff,
+}
+
+impl From<EFLG> for ErrorState {
+    fn from(eflg: EFLG) -> Self {
+        if eflg.txbo() {
+            ErrorState::BusOff
+        } else if eflg.txep() || eflg.rxep() {
+            ErrorState::Passive
+        } else if eflg.ewarn() {
+            ErrorState::Warning
+        } else {
+            ErrorState::Active
+        }
+    }
+}
+
+/// Cause of leaving Sleep mode, see [`MCP25xx::on_wake`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WakeReason {
+    /// A frame on the bus woke the controller, it is in Listen-Only mode
+    BusActivity,
+    /// A mode change was requested through SPI
+    Command,
+}
+
+/// Change of the [`ErrorState`], see [`MCP25xx::poll_error_state`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ErrorEvent {
+    pub previous: ErrorState,
+    pub current: ErrorState,
+}
+
 /// Instruction supported by the CAN controller
 #[derive(Copy, Clone, Debug)]
 #[repr(u8)]
@@ -460,21 +2893,21 @@ pub enum Instruction {
     /// Registers that can be modified with this command implement [`Modify`].
     BitModify = 0b0000_0101,
 
-    #[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
-    #[cfg_attr(doc, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+    #[cfg(feature = "fast_instructions")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
     /// Quick polling command that indicates a filter match and message type
     /// (standard, extended and/or remote) of the received message.
     RxStatus = 0b1011_0000,
-    #[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
-    #[cfg_attr(doc, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+    #[cfg(feature = "fast_instructions")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
     /// When reading a receive buffer, reduces the overhead of a normal `Read`
     /// command by placing the Address Pointer at one of four locations, as
     /// indicated by ‘nm’ in `0b1001_0nm0`.
     ///
     /// Note: The associated RX flag bit (`rxNif` bits in the [`CANINTF`] register) will be cleared after bringing CS high.
     ReadRxBuffer = 0b1001_0000,
-    #[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
-    #[cfg_attr(doc, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+    #[cfg(feature = "fast_instructions")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
     /// When loading a transmit buffer, reduces the overhead of a normal `Write`
     /// command by placing the Address Pointer at one of six locations, as
     /// indicated by ‘abc’ in `0b0100_0abc`.
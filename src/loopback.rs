@@ -0,0 +1,73 @@
+use embedded_hal::blocking::delay::DelayUs;
+
+use crate::registers::OperationMode;
+use crate::{poll_delay, CanFrame, Error, MCP25xx, SpiWithCs, TRANSMIT_POLL_INTERVAL_US};
+
+/// Guard keeping the controller in Loopback mode
+///
+/// Created by [`MCP25xx::loopback_session`].
+/// Restores the previous operation mode when dropped.
+pub struct LoopbackSession<'a, SPI, CS>
+where
+    MCP25xx<SPI, CS>: SpiWithCs,
+{
+    pub(crate) mcp25xx: &'a mut MCP25xx<SPI, CS>,
+    pub(crate) previous_mode: OperationMode,
+}
+
+impl<SPI, CS> LoopbackSession<'_, SPI, CS>
+where
+    MCP25xx<SPI, CS>: SpiWithCs,
+{
+    /// Transmit a frame and return it as received by the controller
+    ///
+    /// Fails with [`Error::Timeout`] if the frame is not received within `timeout_us` microseconds,
+    /// e.g. because the filters reject it.
+    pub fn send_and_recv(
+        &mut self,
+        frame: &CanFrame,
+        delay: &mut impl DelayUs<u32>,
+        timeout_us: u32,
+    ) -> Result<CanFrame, Error<<MCP25xx<SPI, CS> as SpiWithCs>::Error>> {
+        let mut waited = 0;
+        poll(delay, &mut waited, timeout_us, || {
+            self.mcp25xx.try_transmit_tracked(frame)
+        })?;
+        poll(delay, &mut waited, timeout_us, || {
+            embedded_can::Can::try_receive(self.mcp25xx)
+        })
+    }
+
+    /// Operation mode the controller returns to when the guard is dropped
+    #[inline]
+    pub fn previous_mode(&self) -> OperationMode {
+        self.previous_mode
+    }
+}
+
+impl<SPI, CS> Drop for LoopbackSession<'_, SPI, CS>
+where
+    MCP25xx<SPI, CS>: SpiWithCs,
+{
+    fn drop(&mut self) {
+        self.mcp25xx.set_mode(self.previous_mode).ok();
+    }
+}
+
+fn poll<T, E>(
+    delay: &mut impl DelayUs<u32>,
+    waited: &mut u32,
+    timeout_us: u32,
+    mut f: impl FnMut() -> nb::Result<T, E>,
+) -> Result<T, Error<E>> {
+    loop {
+        match f() {
+            Ok(value) => return Ok(value),
+            Err(nb::Error::WouldBlock) => {}
+            Err(nb::Error::Other(e)) => return Err(Error::Spi(e)),
+        }
+        if !poll_delay(delay, TRANSMIT_POLL_INTERVAL_US, waited, timeout_us) {
+            return Err(Error::Timeout);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 #![allow(clippy::identity_op)] // FIXME https://github.com/Robbepop/modular-bitfield/issues/62
+#![allow(unused_parens, unused_braces)] // generated by modular-bitfield on newer compilers
 
 use modular_bitfield::prelude::*;
 
@@ -56,7 +57,7 @@ pub struct RXB1CTRL {
 }
 
 /// Receive Buffer Operating Mode
-#[cfg(not(any(feature = "mcp2515", feature = "mcp25625")))]
+#[cfg(not(feature = "fast_instructions"))]
 #[derive(BitfieldSpecifier, Copy, Clone, Debug)]
 #[bits = 2]
 pub enum RXM {
@@ -71,7 +72,7 @@ pub enum RXM {
 }
 
 /// Receive Buffer Operating Mode
-#[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
+#[cfg(feature = "fast_instructions")]
 #[derive(BitfieldSpecifier, Copy, Clone, Debug)]
 #[bits = 2]
 pub enum RXM {
@@ -84,7 +85,7 @@ pub enum RXM {
 }
 
 /// Can Control Register
-#[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
+#[cfg(feature = "fast_instructions")]
 #[bitfield]
 #[repr(u8)]
 #[derive(Copy, Clone, Debug)]
@@ -93,7 +94,7 @@ pub struct CANCTRL {
     pub clkpre: CLKPRE,
     ///  CLKOUT Pin Enable
     pub clken: bool,
-    #[cfg_attr(doc, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
     /// One-Shot Mode
     pub osm: bool,
     /// Abort All Pending Transmissions
@@ -103,7 +104,7 @@ pub struct CANCTRL {
 }
 
 /// Can Control Register
-#[cfg(not(any(feature = "mcp2515", feature = "mcp25625")))]
+#[cfg(not(feature = "fast_instructions"))]
 #[bitfield]
 #[repr(u8)]
 #[derive(Copy, Clone, Debug)]
@@ -121,12 +122,14 @@ pub struct CANCTRL {
 }
 
 /// Request Operation mode
-#[derive(BitfieldSpecifier, Copy, Clone, Debug)]
+#[derive(BitfieldSpecifier, Copy, Clone, Debug, PartialEq, Eq)]
 #[bits = 3]
 pub enum OperationMode {
     NormalOperation = 0b000,
     Sleep = 0b001,
     Loopback = 0b010,
+    /// Receive without sending acknowledgements or error frames,
+    /// see [`MCP25xx::can_ack_silently`](crate::MCP25xx::can_ack_silently)
     ListenOnly = 0b011,
     Configuration = 0b100,
     Invalid1 = 0b101,
@@ -177,7 +180,7 @@ pub struct CANSTAT {
 }
 
 /// Interrupt Flag Code
-#[derive(BitfieldSpecifier, Copy, Clone, Debug)]
+#[derive(BitfieldSpecifier, Copy, Clone, Debug, PartialEq, Eq)]
 #[bits = 3]
 pub enum InterruptFlagCode {
     NoInterrupt = 0b000,
@@ -190,6 +193,27 @@ pub enum InterruptFlagCode {
     RXB1Interrupt = 0b111,
 }
 
+impl InterruptFlagCode {
+    /// Interrupt source the code stands for, `None` without a pending interrupt
+    ///
+    /// Combined with [`InterruptSource::action`](crate::InterruptSource::action) this dispatches
+    /// an interrupt from a single CANSTAT read, see
+    /// [`MCP25xx::interrupt_code`](crate::MCP25xx::interrupt_code).
+    pub fn source(self) -> Option<crate::InterruptSource> {
+        use crate::InterruptSource;
+        match self {
+            InterruptFlagCode::NoInterrupt => None,
+            InterruptFlagCode::ErrorInterrupt => Some(InterruptSource::Error),
+            InterruptFlagCode::WakeUpInterrupt => Some(InterruptSource::WakeUp),
+            InterruptFlagCode::TXB0Interrupt => Some(InterruptSource::TXB0),
+            InterruptFlagCode::TXB1Interrupt => Some(InterruptSource::TXB1),
+            InterruptFlagCode::TXB2Interrupt => Some(InterruptSource::TXB2),
+            InterruptFlagCode::RXB0Interrupt => Some(InterruptSource::RXB0),
+            InterruptFlagCode::RXB1Interrupt => Some(InterruptSource::RXB1),
+        }
+    }
+}
+
 impl Default for CANSTAT {
     fn default() -> Self {
         0b1000_0000.into()
@@ -210,6 +234,28 @@ pub struct CNF {
 }
 
 impl CNF {
+    /// Combine the three configuration registers
+    ///
+    /// ```
+    /// # use mcp25xx::doctesthelper::get_mcp25xx;
+    /// use mcp25xx::registers::{Register, CNF, CNF1, CNF2, CNF3};
+    ///
+    /// let cnf = CNF::from_parts(
+    ///     CNF1::new().with_sjw(0).with_brp(1),
+    ///     CNF2::new().with_btlmode(true).with_phseg1(2).with_prseg(1),
+    ///     CNF3::new().with_phseg2(2),
+    /// );
+    /// assert_eq!(cnf.into_bytes(), [0x02, 0x91, 0x01]);
+    ///
+    /// // read back the current configuration
+    /// # let mut mcp25xx = get_mcp25xx();
+    /// let mut bytes = [0; 3];
+    /// mcp25xx.read_registers(CNF3::ADDRESS, &mut bytes).unwrap();
+    /// let cnf = CNF::from_bytes(bytes);
+    /// ```
+    pub const fn from_parts(cnf1: CNF1, cnf2: CNF2, cnf3: CNF3) -> Self {
+        CNF { cnf3, cnf2, cnf1 }
+    }
     pub const fn from_bytes(bytes: [u8; 3]) -> Self {
         CNF {
             cnf3: CNF3::from_bytes([bytes[0]]),
@@ -224,6 +270,45 @@ impl CNF {
             self.cnf1.into_bytes()[0],
         ]
     }
+    /// Whether all three registers hold their reset value of zero
+    ///
+    /// No table in [`crate::bitrates`] is all zeros. Zero results in a bit time of 5 time quanta
+    /// of 2 oscillator periods each, e.g. 1.6 Mbps with a 16 MHz oscillator, which no bus uses.
+    /// It is the sign of a controller which was reset and never had its bitrate set.
+    ///
+    /// ```
+    /// use mcp25xx::bitrates::clock_16mhz::CNF_500K_BPS;
+    /// use mcp25xx::registers::CNF;
+    ///
+    /// assert!(CNF::default().is_reset_default());
+    /// assert!(!CNF_500K_BPS.is_reset_default());
+    /// ```
+    pub const fn is_reset_default(&self) -> bool {
+        let bytes = self.into_bytes();
+        bytes[0] == 0 && bytes[1] == 0 && bytes[2] == 0
+    }
+    /// Select where the length of Phase Segment 2 comes from (BTLMODE bit of CNF2)
+    ///
+    /// ```
+    /// use mcp25xx::bitrates::clock_16mhz::CNF_500K_BPS;
+    /// use mcp25xx::registers::PhSeg2Source;
+    ///
+    /// let cnf = CNF_500K_BPS.with_btl_mode(PhSeg2Source::MaxOfPhSeg1AndIpt);
+    /// assert!(!cnf.cnf2.btlmode());
+    /// ```
+    pub fn with_btl_mode(mut self, source: PhSeg2Source) -> Self {
+        self.cnf2.set_btlmode(source == PhSeg2Source::Cnf3);
+        self
+    }
+}
+
+/// Source of the Phase Segment 2 length
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PhSeg2Source {
+    /// The greater of PS1 and the information processing time of 2 TQ
+    MaxOfPhSeg1AndIpt,
+    /// The `phseg2` field of [`CNF3`]
+    Cnf3,
 }
 
 /// Configuration 1 Register
@@ -233,9 +318,9 @@ impl CNF {
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, Default)]
 pub struct CNF1 {
-    /// Baud Rate Prescaler
+    /// Baud Rate Prescaler, a time quantum lasts `2 * (brp + 1)` oscillator periods
     pub brp: B6,
-    /// Synchronization Jump Width Length
+    /// Synchronization Jump Width Length of `sjw + 1` time quanta
     pub sjw: B2,
 }
 
@@ -259,7 +344,7 @@ pub struct CNF2 {
 /// Configuration 3 Register
 ///
 /// Note: Write operations require Configuration mode
-#[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
+#[cfg(feature = "fast_instructions")]
 #[bitfield]
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, Default)]
@@ -271,7 +356,7 @@ pub struct CNF3 {
     __: B3,
     /// Wake-up Filter
     pub wakfil: bool,
-    #[cfg_attr(doc, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
     /// Start-of-Frame Signal
     pub sof: bool,
 }
@@ -279,7 +364,7 @@ pub struct CNF3 {
 /// Configuration 3 Register
 ///
 /// Note: Write operations require Configuration mode
-#[cfg(not(any(feature = "mcp2515", feature = "mcp25625")))]
+#[cfg(not(feature = "fast_instructions"))]
 #[bitfield]
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, Default)]
@@ -504,6 +589,10 @@ impl From<REC> for u8 {
 }
 
 /// Read Status Response Bitfield
+///
+/// | bit 7   | bit 6    | bit 5   | bit 4    | bit 3   | bit 2    | bit 1   | bit 0   |
+/// |---------|----------|---------|----------|---------|----------|---------|---------|
+/// | `tx2if` | `txreq2` | `tx1if` | `txreq1` | `tx0if` | `txreq0` | `rx1if` | `rx0if` |
 #[bitfield]
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, Default)]
@@ -526,13 +615,29 @@ pub struct ReadStatusResponse {
     pub tx2if: bool,
 }
 
+impl ReadStatusResponse {
+    /// Raw status byte, laid out as in the table above
+    ///
+    /// ```
+    /// use mcp25xx::registers::ReadStatusResponse;
+    ///
+    /// let status = ReadStatusResponse::new().with_rx1if(true).with_txreq2(true);
+    /// assert_eq!(status.bits(), 0b0100_0010);
+    /// ```
+    #[inline]
+    pub const fn bits(&self) -> u8 {
+        self.into_bytes()[0]
+    }
+}
+
 /// Read Status Response Bitfield
-#[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
-#[cfg_attr(doc, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+#[cfg(feature = "fast_instructions")]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
 #[bitfield]
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, Default)]
 pub struct RxStatusResponse {
+    /// Filter that accepted the received message
     pub filter_match: FilterMatch,
     pub is_remote: bool,
     pub is_extended: bool,
@@ -545,9 +650,9 @@ pub struct RxStatusResponse {
 }
 
 /// The filter that matched the received message
-#[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
-#[cfg_attr(doc, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
-#[derive(BitfieldSpecifier, Copy, Clone, Debug)]
+#[cfg(feature = "fast_instructions")]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+#[derive(BitfieldSpecifier, Copy, Clone, Debug, PartialEq, Eq)]
 #[bits = 3]
 pub enum FilterMatch {
     RXF0,
@@ -560,6 +665,52 @@ pub enum FilterMatch {
     RXF1Rollover,
 }
 
+/// Kind of the received message
+#[cfg(feature = "fast_instructions")]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MessageType {
+    StandardData,
+    StandardRemote,
+    ExtendedData,
+    ExtendedRemote,
+}
+
+/// Receive buffers holding a message
+#[cfg(feature = "fast_instructions")]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "mcp2515", feature = "mcp25625"))))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReceivedBuffers {
+    RXB0,
+    RXB1,
+    Both,
+}
+
+#[cfg(feature = "fast_instructions")]
+impl RxStatusResponse {
+    /// Receive buffers holding a message, `None` if both are empty
+    pub fn received_buffer(&self) -> Option<ReceivedBuffers> {
+        match (self.rx0if(), self.rx1if()) {
+            (false, false) => None,
+            (true, false) => Some(ReceivedBuffers::RXB0),
+            (false, true) => Some(ReceivedBuffers::RXB1),
+            (true, true) => Some(ReceivedBuffers::Both),
+        }
+    }
+
+    /// Kind of the received message
+    ///
+    /// Only meaningful if [`RxStatusResponse::received_buffer`] is not `None`.
+    pub fn message_type(&self) -> MessageType {
+        match (self.is_extended(), self.is_remote()) {
+            (false, false) => MessageType::StandardData,
+            (false, true) => MessageType::StandardRemote,
+            (true, false) => MessageType::ExtendedData,
+            (true, true) => MessageType::ExtendedRemote,
+        }
+    }
+}
+
 impl Register for RXB0CTRL {
     const ADDRESS: u8 = 0x60;
 }
@@ -626,3 +777,283 @@ impl Modify for CANINTF {}
 impl Modify for EFLG {}
 impl Modify for BFPCTRL {}
 impl Modify for TXRTSCTRL {}
+
+/// Names of the registers 0x00 to 0x7F, indexed by address
+///
+/// Every row of 16 addresses ends with a mirror of [`CANSTAT`] and [`CANCTRL`].
+const REGISTER_NAMES: [&str; 0x80] = [
+    "RXF0SIDH",
+    "RXF0SIDL",
+    "RXF0EID8",
+    "RXF0EID0",
+    "RXF1SIDH",
+    "RXF1SIDL",
+    "RXF1EID8",
+    "RXF1EID0",
+    "RXF2SIDH",
+    "RXF2SIDL",
+    "RXF2EID8",
+    "RXF2EID0",
+    "BFPCTRL",
+    "TXRTSCTRL",
+    "CANSTAT",
+    "CANCTRL",
+    "RXF3SIDH",
+    "RXF3SIDL",
+    "RXF3EID8",
+    "RXF3EID0",
+    "RXF4SIDH",
+    "RXF4SIDL",
+    "RXF4EID8",
+    "RXF4EID0",
+    "RXF5SIDH",
+    "RXF5SIDL",
+    "RXF5EID8",
+    "RXF5EID0",
+    "TEC",
+    "REC",
+    "CANSTAT",
+    "CANCTRL",
+    "RXM0SIDH",
+    "RXM0SIDL",
+    "RXM0EID8",
+    "RXM0EID0",
+    "RXM1SIDH",
+    "RXM1SIDL",
+    "RXM1EID8",
+    "RXM1EID0",
+    "CNF3",
+    "CNF2",
+    "CNF1",
+    "CANINTE",
+    "CANINTF",
+    "EFLG",
+    "CANSTAT",
+    "CANCTRL",
+    "TXB0CTRL",
+    "TXB0SIDH",
+    "TXB0SIDL",
+    "TXB0EID8",
+    "TXB0EID0",
+    "TXB0DLC",
+    "TXB0D0",
+    "TXB0D1",
+    "TXB0D2",
+    "TXB0D3",
+    "TXB0D4",
+    "TXB0D5",
+    "TXB0D6",
+    "TXB0D7",
+    "CANSTAT",
+    "CANCTRL",
+    "TXB1CTRL",
+    "TXB1SIDH",
+    "TXB1SIDL",
+    "TXB1EID8",
+    "TXB1EID0",
+    "TXB1DLC",
+    "TXB1D0",
+    "TXB1D1",
+    "TXB1D2",
+    "TXB1D3",
+    "TXB1D4",
+    "TXB1D5",
+    "TXB1D6",
+    "TXB1D7",
+    "CANSTAT",
+    "CANCTRL",
+    "TXB2CTRL",
+    "TXB2SIDH",
+    "TXB2SIDL",
+    "TXB2EID8",
+    "TXB2EID0",
+    "TXB2DLC",
+    "TXB2D0",
+    "TXB2D1",
+    "TXB2D2",
+    "TXB2D3",
+    "TXB2D4",
+    "TXB2D5",
+    "TXB2D6",
+    "TXB2D7",
+    "CANSTAT",
+    "CANCTRL",
+    "RXB0CTRL",
+    "RXB0SIDH",
+    "RXB0SIDL",
+    "RXB0EID8",
+    "RXB0EID0",
+    "RXB0DLC",
+    "RXB0D0",
+    "RXB0D1",
+    "RXB0D2",
+    "RXB0D3",
+    "RXB0D4",
+    "RXB0D5",
+    "RXB0D6",
+    "RXB0D7",
+    "CANSTAT",
+    "CANCTRL",
+    "RXB1CTRL",
+    "RXB1SIDH",
+    "RXB1SIDL",
+    "RXB1EID8",
+    "RXB1EID0",
+    "RXB1DLC",
+    "RXB1D0",
+    "RXB1D1",
+    "RXB1D2",
+    "RXB1D3",
+    "RXB1D4",
+    "RXB1D5",
+    "RXB1D6",
+    "RXB1D7",
+    "CANSTAT",
+    "CANCTRL",
+];
+
+/// Address and name of every register, in address order
+///
+/// The names follow the datasheet, matching the layout of [`MCP25xx::dump_registers`](crate::MCP25xx::dump_registers).
+///
+/// ```
+/// use mcp25xx::registers::register_map;
+///
+/// let (address, name) = register_map().find(|&(_, name)| name == "CNF1").unwrap();
+/// assert_eq!(address, 0x2A);
+/// ```
+pub fn register_map() -> impl Iterator<Item = (u8, &'static str)> {
+    (0..).zip(REGISTER_NAMES)
+}
+
+/// Address of every register, named as in the datasheet
+///
+/// Self-documenting alternative to raw addresses, e.g. for
+/// [`MCP25xx::read_registers_from`](crate::MCP25xx::read_registers_from).
+/// [`CANSTAT`] and [`CANCTRL`] are also mirrored at the end of every row of 16 addresses,
+/// only their first address is listed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RegisterAddress {
+    RXF0SIDH = 0x00,
+    RXF0SIDL = 0x01,
+    RXF0EID8 = 0x02,
+    RXF0EID0 = 0x03,
+    RXF1SIDH = 0x04,
+    RXF1SIDL = 0x05,
+    RXF1EID8 = 0x06,
+    RXF1EID0 = 0x07,
+    RXF2SIDH = 0x08,
+    RXF2SIDL = 0x09,
+    RXF2EID8 = 0x0A,
+    RXF2EID0 = 0x0B,
+    BFPCTRL = 0x0C,
+    TXRTSCTRL = 0x0D,
+    CANSTAT = 0x0E,
+    CANCTRL = 0x0F,
+    RXF3SIDH = 0x10,
+    RXF3SIDL = 0x11,
+    RXF3EID8 = 0x12,
+    RXF3EID0 = 0x13,
+    RXF4SIDH = 0x14,
+    RXF4SIDL = 0x15,
+    RXF4EID8 = 0x16,
+    RXF4EID0 = 0x17,
+    RXF5SIDH = 0x18,
+    RXF5SIDL = 0x19,
+    RXF5EID8 = 0x1A,
+    RXF5EID0 = 0x1B,
+    TEC = 0x1C,
+    REC = 0x1D,
+    RXM0SIDH = 0x20,
+    RXM0SIDL = 0x21,
+    RXM0EID8 = 0x22,
+    RXM0EID0 = 0x23,
+    RXM1SIDH = 0x24,
+    RXM1SIDL = 0x25,
+    RXM1EID8 = 0x26,
+    RXM1EID0 = 0x27,
+    CNF3 = 0x28,
+    CNF2 = 0x29,
+    CNF1 = 0x2A,
+    CANINTE = 0x2B,
+    CANINTF = 0x2C,
+    EFLG = 0x2D,
+    TXB0CTRL = 0x30,
+    TXB0SIDH = 0x31,
+    TXB0SIDL = 0x32,
+    TXB0EID8 = 0x33,
+    TXB0EID0 = 0x34,
+    TXB0DLC = 0x35,
+    TXB0D0 = 0x36,
+    TXB0D1 = 0x37,
+    TXB0D2 = 0x38,
+    TXB0D3 = 0x39,
+    TXB0D4 = 0x3A,
+    TXB0D5 = 0x3B,
+    TXB0D6 = 0x3C,
+    TXB0D7 = 0x3D,
+    TXB1CTRL = 0x40,
+    TXB1SIDH = 0x41,
+    TXB1SIDL = 0x42,
+    TXB1EID8 = 0x43,
+    TXB1EID0 = 0x44,
+    TXB1DLC = 0x45,
+    TXB1D0 = 0x46,
+    TXB1D1 = 0x47,
+    TXB1D2 = 0x48,
+    TXB1D3 = 0x49,
+    TXB1D4 = 0x4A,
+    TXB1D5 = 0x4B,
+    TXB1D6 = 0x4C,
+    TXB1D7 = 0x4D,
+    TXB2CTRL = 0x50,
+    TXB2SIDH = 0x51,
+    TXB2SIDL = 0x52,
+    TXB2EID8 = 0x53,
+    TXB2EID0 = 0x54,
+    TXB2DLC = 0x55,
+    TXB2D0 = 0x56,
+    TXB2D1 = 0x57,
+    TXB2D2 = 0x58,
+    TXB2D3 = 0x59,
+    TXB2D4 = 0x5A,
+    TXB2D5 = 0x5B,
+    TXB2D6 = 0x5C,
+    TXB2D7 = 0x5D,
+    RXB0CTRL = 0x60,
+    RXB0SIDH = 0x61,
+    RXB0SIDL = 0x62,
+    RXB0EID8 = 0x63,
+    RXB0EID0 = 0x64,
+    RXB0DLC = 0x65,
+    RXB0D0 = 0x66,
+    RXB0D1 = 0x67,
+    RXB0D2 = 0x68,
+    RXB0D3 = 0x69,
+    RXB0D4 = 0x6A,
+    RXB0D5 = 0x6B,
+    RXB0D6 = 0x6C,
+    RXB0D7 = 0x6D,
+    RXB1CTRL = 0x70,
+    RXB1SIDH = 0x71,
+    RXB1SIDL = 0x72,
+    RXB1EID8 = 0x73,
+    RXB1EID0 = 0x74,
+    RXB1DLC = 0x75,
+    RXB1D0 = 0x76,
+    RXB1D1 = 0x77,
+    RXB1D2 = 0x78,
+    RXB1D3 = 0x79,
+    RXB1D4 = 0x7A,
+    RXB1D5 = 0x7B,
+    RXB1D6 = 0x7C,
+    RXB1D7 = 0x7D,
+}
+
+impl From<RegisterAddress> for u8 {
+    #[inline]
+    fn from(address: RegisterAddress) -> u8 {
+        address as u8
+    }
+}
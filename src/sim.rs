@@ -0,0 +1,109 @@
+use core::convert::Infallible;
+
+use crate::CanFrame;
+
+/// In-memory CAN controller for testing application code on the host
+///
+/// Frames transmitted through [`embedded_can::Can`] are queued and can be taken out with
+/// [`SimController::pop_transmitted`], frames queued with [`SimController::inject`] are handed
+/// out by `try_receive`. Each queue holds up to `N` frames.
+///
+/// ```
+/// use embedded_can::{Can, Frame, StandardId};
+/// use mcp25xx::sim::SimController;
+/// use mcp25xx::CanFrame;
+///
+/// let mut can: SimController = SimController::new();
+///
+/// let frame = CanFrame::new(StandardId::new(123).unwrap(), &[1, 2, 3]).unwrap();
+/// can.inject(frame).unwrap();
+///
+/// let received = can.try_receive().unwrap();
+/// can.try_transmit(&received).unwrap();
+/// assert_eq!(can.pop_transmitted().unwrap().data(), &[1, 2, 3]);
+/// ```
+pub struct SimController<const N: usize = 8> {
+    tx: Queue<N>,
+    rx: Queue<N>,
+}
+
+impl<const N: usize> SimController<N> {
+    pub fn new() -> Self {
+        SimController {
+            tx: Queue::new(),
+            rx: Queue::new(),
+        }
+    }
+
+    /// Queue a frame as if it was received from the bus
+    ///
+    /// Returns the frame if the receive queue is full.
+    pub fn inject(&mut self, frame: CanFrame) -> Result<(), CanFrame> {
+        self.rx.push(frame)
+    }
+
+    /// Take the oldest frame transmitted by the application
+    pub fn pop_transmitted(&mut self) -> Option<CanFrame> {
+        self.tx.pop()
+    }
+}
+
+impl<const N: usize> Default for SimController<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> embedded_can::Can for SimController<N> {
+    type Frame = CanFrame;
+    type Error = Infallible;
+
+    fn try_transmit(&mut self, frame: &Self::Frame) -> nb::Result<Option<Self::Frame>, Infallible> {
+        self.tx
+            .push(frame.clone())
+            .map_err(|_| nb::Error::WouldBlock)?;
+        Ok(None)
+    }
+
+    fn try_receive(&mut self) -> nb::Result<Self::Frame, Infallible> {
+        self.rx.pop().ok_or(nb::Error::WouldBlock)
+    }
+}
+
+impl<const N: usize> embedded_can::blocking::Default for SimController<N> {}
+
+/// Fixed size ring buffer of frames
+struct Queue<const N: usize> {
+    frames: [CanFrame; N],
+    head: usize,
+    len: usize,
+}
+
+impl<const N: usize> Queue<N> {
+    fn new() -> Self {
+        Queue {
+            frames: core::array::from_fn(|_| CanFrame::default()),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, frame: CanFrame) -> Result<(), CanFrame> {
+        if self.len == N {
+            return Err(frame);
+        }
+        self.frames[(self.head + self.len) % N] = frame;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<CanFrame> {
+        if self.len == 0 {
+            return None;
+        }
+        let frame = core::mem::take(&mut self.frames[self.head]);
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(frame)
+    }
+}
@@ -0,0 +1,71 @@
+use core::convert::TryFrom;
+
+use embedded_can::{ExtendedId, Frame, Id, StandardId};
+use socketcan::EmbeddedFrame;
+
+use crate::CanFrame;
+
+fn to_socketcan_id(id: Id) -> socketcan::Id {
+    // SAFETY:
+    // both crates enforce the same ranges for their ids
+    unsafe {
+        match id {
+            Id::Standard(id) => socketcan::StandardId::new_unchecked(id.as_raw()).into(),
+            Id::Extended(id) => socketcan::ExtendedId::new_unchecked(id.as_raw()).into(),
+        }
+    }
+}
+
+fn from_socketcan_id(id: socketcan::Id) -> Id {
+    // SAFETY:
+    // both crates enforce the same ranges for their ids
+    unsafe {
+        match id {
+            socketcan::Id::Standard(id) => StandardId::new_unchecked(id.as_raw()).into(),
+            socketcan::Id::Extended(id) => ExtendedId::new_unchecked(id.as_raw()).into(),
+        }
+    }
+}
+
+impl From<&CanFrame> for socketcan::CanFrame {
+    fn from(frame: &CanFrame) -> Self {
+        let id = to_socketcan_id(Frame::id(frame));
+        let frame = if Frame::is_remote_frame(frame) {
+            socketcan::CanFrame::new_remote(id, Frame::dlc(frame))
+        } else {
+            socketcan::CanFrame::new(id, Frame::data(frame))
+        };
+        // CanFrame never holds more than 8 data bytes
+        frame.unwrap()
+    }
+}
+
+impl From<CanFrame> for socketcan::CanFrame {
+    #[inline]
+    fn from(frame: CanFrame) -> Self {
+        (&frame).into()
+    }
+}
+
+/// Fails for error frames, which have no equivalent on the CAN controller
+impl TryFrom<&socketcan::CanFrame> for CanFrame {
+    type Error = ();
+
+    fn try_from(frame: &socketcan::CanFrame) -> Result<Self, ()> {
+        let id = from_socketcan_id(frame.id());
+        match frame {
+            socketcan::CanFrame::Data(frame) => <CanFrame as Frame>::new(id, frame.data()),
+            socketcan::CanFrame::Remote(frame) => <CanFrame as Frame>::new_remote(id, frame.dlc()),
+            socketcan::CanFrame::Error(_) => Err(()),
+        }
+    }
+}
+
+impl TryFrom<socketcan::CanFrame> for CanFrame {
+    type Error = ();
+
+    #[inline]
+    fn try_from(frame: socketcan::CanFrame) -> Result<Self, ()> {
+        CanFrame::try_from(&frame)
+    }
+}
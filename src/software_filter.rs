@@ -0,0 +1,83 @@
+use embedded_can::Id;
+
+/// Acceptance filters applied by the driver instead of the controller
+///
+/// Holds up to `N` id and mask pairs, used by [`MCP25xx::receive_sw_filtered`](crate::MCP25xx::receive_sw_filtered).
+/// Frames still have to pass the hardware filters first.
+///
+/// ```
+/// use embedded_can::{ExtendedId, StandardId};
+/// use mcp25xx::SoftwareFilterSet;
+///
+/// let mut filters: SoftwareFilterSet<16> = SoftwareFilterSet::new();
+/// filters.add(StandardId::new(0x100).unwrap(), 0x7F0).unwrap();
+/// filters.add(ExtendedId::new(0x18FE_F100).unwrap(), 0x1FFF_FFFF).unwrap();
+///
+/// assert!(filters.matches(StandardId::new(0x10A).unwrap().into()));
+/// assert!(!filters.matches(StandardId::new(0x200).unwrap().into()));
+/// ```
+pub struct SoftwareFilterSet<const N: usize> {
+    filters: [Option<(Id, u32)>; N],
+    len: usize,
+}
+
+impl<const N: usize> SoftwareFilterSet<N> {
+    pub const fn new() -> Self {
+        SoftwareFilterSet {
+            filters: [None; N],
+            len: 0,
+        }
+    }
+
+    /// Add a filter accepting ids which equal `id` in all bits set in `mask`
+    ///
+    /// Returns the filter if the set is full.
+    pub fn add(&mut self, id: impl Into<Id>, mask: u32) -> Result<(), (Id, u32)> {
+        let id = id.into();
+        if self.len == N {
+            return Err((id, mask));
+        }
+        self.filters[self.len] = Some((id, mask));
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Remove all filters, afterwards no frame matches
+    pub fn clear(&mut self) {
+        self.filters = [None; N];
+        self.len = 0;
+    }
+
+    /// Number of filters
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether a frame with the given id passes any of the filters
+    pub fn matches(&self, id: Id) -> bool {
+        self.filters[..self.len]
+            .iter()
+            .flatten()
+            .any(|&(filter, mask)| match (filter, id) {
+                (Id::Standard(filter), Id::Standard(id)) => {
+                    (filter.as_raw() ^ id.as_raw()) as u32 & mask == 0
+                }
+                (Id::Extended(filter), Id::Extended(id)) => {
+                    (filter.as_raw() ^ id.as_raw()) & mask == 0
+                }
+                _ => false,
+            })
+    }
+}
+
+impl<const N: usize> Default for SoftwareFilterSet<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,139 @@
+use core::fmt::Debug;
+
+use embedded_hal::blocking::spi::{Transfer, Write};
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::{Instruction, MCP25xx, SpiWithCs};
+
+/// Adapter for SPI peripherals that only support 16 bit words
+///
+/// The byte stream of each transaction is packed into 16 bit words, most significant byte first.
+/// Configure the peripheral for MSB first transfers with the usual SPI mode 0 or 3.
+/// CS framing stays the same as for 8 bit transfers, so chip select has to be driven by a GPIO.
+///
+/// Transactions with an odd number of bytes are padded:
+/// * A padded read clocks in the next register early, it is handed out by the following read.
+/// * The last byte of a `Write` instruction is sent as a separate `BitModify` instruction with
+///   a mask of `0xFF`, which writes the whole register, so no register is overwritten by padding.
+/// * Other instructions are padded with a zero byte which the controller ignores.
+///
+/// ```
+/// # use mcp25xx::doctesthelper::{NoOpCS, NoOpSPI16};
+/// use mcp25xx::{MCP25xx, Spi16};
+///
+/// # let spi = NoOpSPI16;
+/// # let cs = NoOpCS;
+/// // spi implements the embedded_hal SPI traits for u16 words
+/// let mut mcp25xx = MCP25xx::new(Spi16::new(spi), cs);
+/// mcp25xx.reset().unwrap();
+/// ```
+pub struct Spi16<SPI> {
+    spi: SPI,
+    /// Byte waiting for its partner to complete a word
+    pending: Option<u8>,
+    /// Byte already clocked in by padding a read
+    read_ahead: Option<u8>,
+    /// First two bytes of the current transaction
+    header: [u8; 2],
+    /// Number of bytes written in the current transaction
+    written: usize,
+}
+
+impl<SPI> Spi16<SPI> {
+    pub fn new(spi: SPI) -> Self {
+        Spi16 {
+            spi,
+            pending: None,
+            read_ahead: None,
+            header: [0; 2],
+            written: 0,
+        }
+    }
+
+    /// Return the wrapped SPI peripheral
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI, CS> SpiWithCs for MCP25xx<Spi16<SPI>, CS>
+where
+    SPI: Transfer<u16>,
+    SPI: Write<u16, Error = <SPI as Transfer<u16>>::Error>,
+    <SPI as Transfer<u16>>::Error: Debug,
+    CS: OutputPin,
+{
+    type Error = <SPI as Transfer<u16>>::Error;
+
+    fn set_cs_high(&mut self) {
+        if let Some(byte) = self.spi.pending.take() {
+            let [instruction, address] = self.spi.header;
+            if instruction == Instruction::Write as u8 && self.spi.written >= 3 {
+                // finish the write with a separate BitModify instruction
+                let address = address.wrapping_add(self.spi.written as u8 - 3);
+                self.cs.set_high().ok();
+                self.cs.set_low().ok();
+                self.spi
+                    .spi
+                    .write(&[
+                        u16::from_be_bytes([Instruction::BitModify as u8, address]),
+                        u16::from_be_bytes([0xFF, byte]),
+                    ])
+                    .ok();
+            } else {
+                self.spi.spi.write(&[u16::from_be_bytes([byte, 0])]).ok();
+            }
+        }
+        self.spi.read_ahead = None;
+        self.spi.written = 0;
+        self.cs.set_high().ok();
+    }
+
+    #[inline]
+    fn set_cs_low(&mut self) {
+        self.cs.set_low().ok();
+    }
+
+    fn spi_write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        for &byte in words {
+            if let Some(header) = self.spi.header.get_mut(self.spi.written) {
+                *header = byte;
+            }
+            self.spi.written += 1;
+            match self.spi.pending.take() {
+                Some(pending) => self.spi.spi.write(&[u16::from_be_bytes([pending, byte])])?,
+                None => self.spi.pending = Some(byte),
+            }
+        }
+        Ok(())
+    }
+
+    fn spi_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        if words.is_empty() {
+            return Ok(words);
+        }
+        let mut i = 0;
+        if let Some(byte) = self.spi.read_ahead.take() {
+            words[0] = byte;
+            i = 1;
+        } else if let Some(pending) = self.spi.pending.take() {
+            let mut word = [u16::from_be_bytes([pending, words[0]])];
+            words[0] = self.spi.spi.transfer(&mut word)?[0] as u8;
+            i = 1;
+        }
+        while i + 1 < words.len() {
+            let mut word = [u16::from_be_bytes([words[i], words[i + 1]])];
+            let [high, low] = self.spi.spi.transfer(&mut word)?[0].to_be_bytes();
+            words[i] = high;
+            words[i + 1] = low;
+            i += 2;
+        }
+        if i < words.len() {
+            let mut word = [u16::from_be_bytes([words[i], 0])];
+            let [high, low] = self.spi.spi.transfer(&mut word)?[0].to_be_bytes();
+            words[i] = high;
+            self.spi.read_ahead = Some(low);
+        }
+        Ok(words)
+    }
+}
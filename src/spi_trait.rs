@@ -6,6 +6,79 @@ use embedded_hal::digital::v2::OutputPin;
 
 use crate::MCP25xx;
 
+/// Chip select pin wrapper for boards where CS is active-high
+///
+/// Standard boards have an active-low CS and never need this.
+/// Only use it if an inverting buffer or level shifter sits between MCU and controller.
+/// See [`MCP25xx::new_active_high_cs`].
+pub struct ActiveHighCs<CS>(pub CS);
+
+impl<CS: OutputPin> OutputPin for ActiveHighCs<CS> {
+    type Error = CS::Error;
+
+    #[inline]
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.set_high()
+    }
+
+    #[inline]
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.set_low()
+    }
+}
+
+/// Chip select replacement for designs where CS is driven outside of the driver
+///
+/// Every instruction still has to be framed by CS, otherwise prefer [`CsFn`].
+/// See [`MCP25xx::new_external_cs`].
+pub struct NoCs;
+
+impl OutputPin for NoCs {
+    type Error = Infallible;
+
+    #[inline]
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    #[inline]
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Chip select driven by a closure, e.g. for daisy-chained or multiplexed SPI buses
+///
+/// The closure is called with `true` before and `false` after each instruction,
+/// it has to select the controller and deselect it in the same way a CS pin would.
+///
+/// ```
+/// # use embedded_hal_mock::spi::Mock;
+/// use mcp25xx::{CsFn, MCP25xx};
+///
+/// # let spi = Mock::new(&[]);
+/// let mcp25xx = MCP25xx::new(spi, CsFn(|select: bool| {
+///     // route the parent bus to the controller while `select` is true
+/// }));
+/// ```
+pub struct CsFn<F>(pub F);
+
+impl<F: FnMut(bool)> OutputPin for CsFn<F> {
+    type Error = Infallible;
+
+    #[inline]
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        (self.0)(true);
+        Ok(())
+    }
+
+    #[inline]
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        (self.0)(false);
+        Ok(())
+    }
+}
+
 /// Convenience trait, reducing the amount of where bounds for generic code
 pub trait SpiWithCs {
     type Error: Debug;
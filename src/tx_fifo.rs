@@ -0,0 +1,115 @@
+use crate::{tx_buffer_free, CanFrame, MCP25xx, SpiWithCs, TxBuffer};
+
+/// Software queue of frames waiting for a free transmit buffer
+///
+/// Frames are pushed by the application and handed to the controller by [`TxFifo::service`],
+/// typically called from the main loop or the interrupt handler. The queue holds up to `N`
+/// frames without allocating.
+///
+/// ```
+/// use embedded_can::{Frame, StandardId};
+/// use mcp25xx::{CanFrame, TxFifo};
+/// # let mut mcp25xx = mcp25xx::doctesthelper::get_mcp25xx();
+///
+/// let mut fifo: TxFifo<16> = TxFifo::new();
+/// for i in 0..4 {
+///     let frame = CanFrame::new(StandardId::new(0x100 + i).unwrap(), &[]).unwrap();
+///     fifo.push(frame).unwrap();
+/// }
+///
+/// // All three transmit buffers are free, so three frames are loaded
+/// assert_eq!(fifo.service(&mut mcp25xx).unwrap(), 3);
+/// assert_eq!(fifo.len(), 1);
+/// ```
+pub struct TxFifo<const N: usize> {
+    frames: [CanFrame; N],
+    len: usize,
+}
+
+impl<const N: usize> TxFifo<N> {
+    pub fn new() -> Self {
+        TxFifo {
+            frames: core::array::from_fn(|_| CanFrame::default()),
+            len: 0,
+        }
+    }
+
+    /// Queue a frame for transmission
+    ///
+    /// Returns the frame if the queue is full, the producer has to wait for
+    /// [`TxFifo::service`] to make room.
+    pub fn push(&mut self, frame: CanFrame) -> Result<(), CanFrame> {
+        if self.len == N {
+            return Err(frame);
+        }
+        self.frames[self.len] = frame;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Number of queued frames
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Load queued frames into all free transmit buffers and request to send them
+    ///
+    /// Frames with the highest bus priority are loaded first. Returns the number of frames loaded.
+    pub fn service<SPI, CS>(
+        &mut self,
+        mcp25xx: &mut MCP25xx<SPI, CS>,
+    ) -> Result<usize, <MCP25xx<SPI, CS> as SpiWithCs>::Error>
+    where
+        MCP25xx<SPI, CS>: SpiWithCs,
+    {
+        if self.is_empty() {
+            return Ok(0);
+        }
+        let status = mcp25xx.read_status()?;
+        let mut loaded = 0;
+        for buf_idx in [TxBuffer::TXB2, TxBuffer::TXB1, TxBuffer::TXB0] {
+            if self.is_empty() {
+                break;
+            }
+            if !tx_buffer_free(status, buf_idx) {
+                continue;
+            }
+            let next = self.next();
+            mcp25xx.load_and_send(buf_idx, &self.frames[next])?;
+            self.remove(next);
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// Index of the first queued frame with the highest bus priority
+    fn next(&self) -> usize {
+        (0..self.len)
+            .min_by_key(|&i| self.frames[i].priority())
+            .unwrap_or(0)
+    }
+
+    /// Remove the frame at `index`, keeping the order of the remaining frames
+    fn remove(&mut self, index: usize) {
+        self.frames[index..self.len].rotate_left(1);
+        self.len -= 1;
+        self.frames[self.len] = CanFrame::default();
+    }
+}
+
+impl<const N: usize> Default for TxFifo<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
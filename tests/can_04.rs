@@ -0,0 +1,50 @@
+#![cfg(feature = "embedded-can-04")]
+
+use embedded_hal_mock::spi::Transaction;
+use mcp25xx::embedded_can_04::nb::Can;
+use mcp25xx::embedded_can_04::{Frame, Id, StandardId};
+use mcp25xx::{CanFrame, Instruction};
+
+use crate::mock_bus::get_mock_bus;
+
+#[allow(dead_code)]
+mod mock_bus;
+
+#[test]
+fn test_frame() {
+    let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[1, 2]).unwrap();
+    assert_eq!(frame.id(), Id::Standard(StandardId::new(0x123).unwrap()));
+    assert_eq!(frame.data(), [1, 2]);
+    assert!(CanFrame::new(StandardId::ZERO, &[0; 9]).is_none());
+
+    let frame = CanFrame::new_remote(StandardId::ZERO, 4).unwrap();
+    assert!(frame.is_remote_frame());
+    assert_eq!(frame.dlc(), 4);
+}
+
+#[test]
+fn test_transmit_receive() {
+    #[cfg(feature = "fast_instructions")]
+    let load_instruction = vec![Instruction::LoadTxBuffer as u8];
+    #[cfg(not(feature = "fast_instructions"))]
+    let load_instruction = vec![Instruction::Write as u8, 0x31];
+
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0]),
+            Transaction::write(load_instruction),
+            Transaction::write(vec![0, 32, 0, 0, 1, 1]),
+            Transaction::write(vec![Instruction::Rts as u8 | 1]),
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0]),
+        ],
+        4,
+    );
+
+    let frame = CanFrame::new(StandardId::new(1).unwrap(), &[1]).unwrap();
+    assert!(bus.transmit(&frame).unwrap().is_none());
+    assert!(matches!(bus.receive(), Err(nb::Error::WouldBlock)));
+    bus.cs.done();
+    bus.spi.done();
+}
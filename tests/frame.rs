@@ -0,0 +1,237 @@
+use embedded_can::{ExtendedId, Frame, Id, StandardId};
+use mcp25xx::{dlc_to_len, len_to_dlc, CanFrame, FrameBuilder, FrameError};
+
+#[test]
+fn test_priority() {
+    let std = |id| StandardId::new(id).unwrap();
+    let ext = |id| ExtendedId::new(id).unwrap();
+
+    let mut frames = [
+        CanFrame::new_remote(std(0x100), 0).unwrap(),
+        CanFrame::new(ext(0x100 << 18 | 1), &[]).unwrap(),
+        CanFrame::new(std(0x101), &[]).unwrap(),
+        CanFrame::new(std(0x100), &[1]).unwrap(),
+        CanFrame::new_remote(ext(0x100 << 18), 0).unwrap(),
+        CanFrame::new(ext(0x100 << 18), &[]).unwrap(),
+        CanFrame::new(ext(0x0FF << 18 | 0x3FFFF), &[]).unwrap(),
+    ];
+    frames.sort_by_key(CanFrame::priority);
+
+    let order: Vec<_> = frames
+        .iter()
+        .map(|frame| (frame.id(), frame.is_remote_frame()))
+        .collect();
+    assert_eq!(
+        order,
+        [
+            (ext(0x0FF << 18 | 0x3FFFF).into(), false),
+            (std(0x100).into(), false),
+            (std(0x100).into(), true),
+            (ext(0x100 << 18).into(), false),
+            (ext(0x100 << 18).into(), true),
+            (ext(0x100 << 18 | 1).into(), false),
+            (std(0x101).into(), false),
+        ]
+    );
+}
+
+#[test]
+fn test_from_raw() {
+    // standard id 0x123, DLC 2
+    let bytes = [0x24, 0x60, 0, 0, 2, 0xAA, 0xBB, 0, 0, 0, 0, 0, 0];
+    let frame = CanFrame::from_raw(&bytes);
+    assert!(frame.is_valid());
+    assert_eq!(frame.id(), StandardId::new(0x123).unwrap().into());
+    assert_eq!(frame.data(), [0xAA, 0xBB]);
+
+    assert!(CanFrame::new(StandardId::ZERO, &[0; 8]).unwrap().is_valid());
+}
+
+#[test]
+fn test_invalid_dlc() {
+    let mut bytes = [0; 13];
+    bytes[4] = 12;
+    let frame = CanFrame::from_raw(&bytes);
+    assert!(!frame.is_valid());
+    assert_eq!(frame.dlc(), 12);
+}
+
+#[test]
+fn test_invalid_dlc_data() {
+    let mut bytes = [0; 13];
+    bytes[4] = 15;
+    bytes[5..].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    let frame = CanFrame::from_raw(&bytes);
+    assert_eq!(frame.dlc(), 15);
+    assert_eq!(frame.data(), [1, 2, 3, 4, 5, 6, 7, 8]);
+    assert!(format!("{:?}", frame).contains("[1, 2, 3, 4, 5, 6, 7, 8]"));
+    assert!(!frame.looks_truncated());
+    assert_eq!(frame.transmission_time_us(500_000), 270);
+}
+
+#[test]
+fn test_with_id() {
+    let remote = CanFrame::new_remote(StandardId::new(0x100).unwrap(), 3).unwrap();
+    let remapped = remote.clone().with_id(StandardId::new(0x200).unwrap());
+    assert_eq!(remapped.id(), StandardId::new(0x200).unwrap().into());
+    assert!(remapped.is_remote_frame());
+    assert_eq!(remapped.dlc(), 3);
+
+    let frame = CanFrame::new(ExtendedId::new(0x1800_0100).unwrap(), &[1, 2, 3]).unwrap();
+    let remapped = frame.with_id(StandardId::new(0x7FF).unwrap());
+    assert!(!remapped.is_extended());
+    assert_eq!(remapped.data(), [1, 2, 3]);
+}
+
+#[test]
+fn test_looks_truncated() {
+    let id = StandardId::new(0x100).unwrap();
+    assert!(CanFrame::new(id, &[1, 2, 0]).unwrap().looks_truncated());
+    assert!(!CanFrame::new(id, &[1, 2, 3]).unwrap().looks_truncated());
+    assert!(!CanFrame::new(id, &[]).unwrap().looks_truncated());
+    assert!(!CanFrame::new_remote(id, 3).unwrap().looks_truncated());
+}
+
+#[test]
+fn test_frame_data() {
+    let ids: [embedded_can::Id; 2] = [
+        StandardId::new(0x7FF).unwrap().into(),
+        ExtendedId::new(0x1FFF_FFFF).unwrap().into(),
+    ];
+    for id in ids {
+        for data in [&[][..], &[0xA5][..], &[1, 2, 3, 4, 5, 6, 7, 8][..]] {
+            let frame = CanFrame::new(id, data).unwrap();
+            assert_eq!(frame.id(), id);
+            assert_eq!(
+                frame.is_extended(),
+                matches!(id, embedded_can::Id::Extended(_))
+            );
+            assert!(!frame.is_remote_frame());
+            assert!(frame.is_data_frame());
+            assert_eq!(frame.dlc(), data.len());
+            assert_eq!(frame.data(), data);
+        }
+        assert!(CanFrame::new(id, &[0; 9]).is_err());
+    }
+}
+
+#[test]
+fn test_frame_remote() {
+    let ids: [embedded_can::Id; 2] = [
+        StandardId::ZERO.into(),
+        ExtendedId::new(0x1234_5678).unwrap().into(),
+    ];
+    for id in ids {
+        for dlc in [0, 1, 8] {
+            let frame = CanFrame::new_remote(id, dlc).unwrap();
+            assert_eq!(frame.id(), id);
+            assert_eq!(
+                frame.is_extended(),
+                matches!(id, embedded_can::Id::Extended(_))
+            );
+            assert!(frame.is_remote_frame());
+            assert!(!frame.is_data_frame());
+            assert_eq!(frame.dlc(), dlc);
+            // remote frames do not carry data
+            assert_eq!(frame.data(), []);
+        }
+        assert!(CanFrame::new_remote(id, 9).is_err());
+    }
+}
+
+#[test]
+fn test_frame_standard_extended_distinct() {
+    // the same raw value as standard and extended id
+    let standard = CanFrame::new(StandardId::new(0x123).unwrap(), &[]).unwrap();
+    let extended = CanFrame::new(ExtendedId::new(0x123).unwrap(), &[]).unwrap();
+    assert!(!standard.is_extended());
+    assert!(extended.is_extended());
+    assert_ne!(standard.id(), extended.id());
+    assert!(standard.is_standard());
+}
+
+#[test]
+fn test_transmission_time() {
+    let standard = CanFrame::new(StandardId::new(0x123).unwrap(), &[0; 8]).unwrap();
+    let extended = CanFrame::new(ExtendedId::new(0x123).unwrap(), &[0; 8]).unwrap();
+    let remote = CanFrame::new_remote(StandardId::new(0x123).unwrap(), 8).unwrap();
+    assert_eq!(standard.transmission_time_us(1_000_000), 135);
+    assert_eq!(extended.transmission_time_us(1_000_000), 160);
+    // no data bytes
+    assert_eq!(remote.transmission_time_us(1_000_000), 55);
+    assert_eq!(standard.transmission_time_us(125_000), 1080);
+    // rounded up
+    assert_eq!(standard.transmission_time_us(83_333), 1621);
+}
+
+#[test]
+#[should_panic]
+fn test_transmission_time_zero_bitrate() {
+    let frame = CanFrame::new(StandardId::new(0x123).unwrap(), &[0; 8]).unwrap();
+    frame.transmission_time_us(0);
+}
+
+#[test]
+fn test_frame_builder() {
+    let frame = CanFrame::builder()
+        .standard(0x123)
+        .data(&[1, 2, 3])
+        .build()
+        .unwrap();
+    assert_eq!(frame.id(), Id::Standard(StandardId::new(0x123).unwrap()));
+    assert!(!frame.is_remote_frame());
+    assert_eq!(frame.data(), [1, 2, 3]);
+
+    let frame = CanFrame::builder()
+        .extended(0x1ABCD)
+        .remote(5)
+        .build()
+        .unwrap();
+    assert_eq!(frame.id(), Id::Extended(ExtendedId::new(0x1ABCD).unwrap()));
+    assert!(frame.is_remote_frame());
+    assert_eq!(frame.dlc(), 5);
+
+    let frame = CanFrame::builder().standard(0x7FF).build().unwrap();
+    assert_eq!(frame.dlc(), 0);
+
+    let error = |builder: FrameBuilder| builder.build().unwrap_err();
+    assert_eq!(error(CanFrame::builder().data(&[1])), FrameError::MissingId);
+    assert_eq!(
+        error(CanFrame::builder().standard(0x800)),
+        FrameError::InvalidId
+    );
+    assert_eq!(
+        error(CanFrame::builder().extended(0x2000_0000)),
+        FrameError::InvalidId
+    );
+    assert_eq!(
+        error(CanFrame::builder().standard(1).data(&[0; 9])),
+        FrameError::TooLong
+    );
+    assert_eq!(
+        error(CanFrame::builder().standard(1).remote(9)),
+        FrameError::TooLong
+    );
+    assert_eq!(
+        error(CanFrame::builder().standard(1).data(&[1]).remote(1)),
+        FrameError::RemoteWithData
+    );
+    // the first error is reported
+    assert_eq!(
+        error(CanFrame::builder().standard(0x800).data(&[0; 9])),
+        FrameError::InvalidId
+    );
+}
+
+#[test]
+fn test_dlc_len_conversion() {
+    for dlc in 0..=8 {
+        assert_eq!(dlc_to_len(dlc), dlc as usize);
+        assert_eq!(len_to_dlc(dlc as usize), dlc);
+    }
+    for dlc in 9..=15 {
+        assert_eq!(dlc_to_len(dlc), 8);
+    }
+    assert_eq!(len_to_dlc(9), 8);
+    assert_eq!(len_to_dlc(usize::MAX), 8);
+}
@@ -10,7 +10,7 @@ pub fn get_mock_bus(
 ) -> MCP25xx<Generic<Transaction>, MockCS> {
     let cs = MockCS::new(cs_toggles);
     let spi = Mock::new(spi_expectations);
-    MCP25xx { spi, cs }
+    MCP25xx::new(spi, cs)
 }
 
 pub struct MockCS(usize);
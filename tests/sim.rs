@@ -0,0 +1,33 @@
+#![cfg(feature = "sim")]
+
+use embedded_can::blocking::Can as _;
+use embedded_can::{Can, ExtendedId, Frame, StandardId};
+use mcp25xx::sim::SimController;
+use mcp25xx::CanFrame;
+
+#[test]
+fn test_sim_queues() {
+    let mut can: SimController<2> = SimController::new();
+    assert!(can.try_receive().is_err());
+
+    let first = CanFrame::new(StandardId::new(1).unwrap(), &[1]).unwrap();
+    let second = CanFrame::new(ExtendedId::new(2).unwrap(), &[2, 2]).unwrap();
+    let third = CanFrame::new_remote(StandardId::new(3).unwrap(), 3).unwrap();
+
+    can.inject(first.clone()).unwrap();
+    can.inject(second.clone()).unwrap();
+    assert!(can.inject(third.clone()).is_err());
+
+    assert_eq!(can.try_read().unwrap().data(), first.data());
+    can.inject(third).unwrap();
+    assert_eq!(can.try_read().unwrap().id(), second.id());
+    assert!(can.try_read().unwrap().is_remote_frame());
+    assert!(can.try_receive().is_err());
+
+    can.try_write(&first).unwrap();
+    can.try_write(&second).unwrap();
+    assert!(can.try_transmit(&first).is_err());
+    assert_eq!(can.pop_transmitted().unwrap().id(), first.id());
+    assert_eq!(can.pop_transmitted().unwrap().id(), second.id());
+    assert!(can.pop_transmitted().is_none());
+}
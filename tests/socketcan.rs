@@ -0,0 +1,44 @@
+#![cfg(feature = "socketcan")]
+
+use std::convert::TryFrom;
+
+use embedded_can::{ExtendedId, Frame, Id, StandardId};
+use mcp25xx::CanFrame;
+
+#[test]
+fn test_socketcan_data_frame() {
+    let frame = CanFrame::new(ExtendedId::new(0x1234567).unwrap(), &[1, 2, 3]).unwrap();
+    let socketcan_frame = socketcan::CanFrame::from(&frame);
+    assert!(socketcan::EmbeddedFrame::is_extended(&socketcan_frame));
+    assert_eq!(
+        socketcan::EmbeddedFrame::id(&socketcan_frame),
+        socketcan::Id::Extended(socketcan::ExtendedId::new(0x1234567).unwrap())
+    );
+    assert_eq!(socketcan::EmbeddedFrame::data(&socketcan_frame), &[1, 2, 3]);
+
+    let frame = CanFrame::try_from(socketcan_frame).unwrap();
+    assert_eq!(
+        frame.id(),
+        Id::Extended(ExtendedId::new(0x1234567).unwrap())
+    );
+    assert_eq!(frame.data(), &[1, 2, 3]);
+}
+
+#[test]
+fn test_socketcan_remote_frame() {
+    let frame = CanFrame::new_remote(StandardId::new(0x123).unwrap(), 4).unwrap();
+    let socketcan_frame = socketcan::CanFrame::from(frame);
+    assert!(socketcan::EmbeddedFrame::is_remote_frame(&socketcan_frame));
+    assert_eq!(socketcan::EmbeddedFrame::dlc(&socketcan_frame), 4);
+
+    let frame = CanFrame::try_from(&socketcan_frame).unwrap();
+    assert!(frame.is_remote_frame());
+    assert_eq!(frame.id(), Id::Standard(StandardId::new(0x123).unwrap()));
+    assert_eq!(frame.dlc(), 4);
+}
+
+#[test]
+fn test_socketcan_error_frame() {
+    let error_frame = socketcan::CanErrorFrame::new_error(0x40, &[0; 8]).unwrap();
+    assert!(CanFrame::try_from(socketcan::CanFrame::Error(error_frame)).is_err());
+}
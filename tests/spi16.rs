@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+
+use embedded_hal::blocking::spi::{Transfer, Write};
+use mcp25xx::registers::*;
+use mcp25xx::{Instruction, MCP25xx, Spi16, SpiWithCs};
+
+use crate::mock_bus::MockCS;
+
+#[allow(dead_code)]
+mod mock_bus;
+
+/// Expects 16 bit words and answers transfers
+struct Mock16(VecDeque<(u16, u16)>);
+
+impl Write<u16> for Mock16 {
+    type Error = Infallible;
+
+    fn write(&mut self, words: &[u16]) -> Result<(), Self::Error> {
+        for &word in words {
+            let (expected, _) = self.0.pop_front().expect("unexpected write");
+            assert_eq!(word, expected, "{:#06X} != {:#06X}", word, expected);
+        }
+        Ok(())
+    }
+}
+
+impl Transfer<u16> for Mock16 {
+    type Error = Infallible;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u16]) -> Result<&'w [u16], Self::Error> {
+        for word in words.iter_mut() {
+            let (expected, response) = self.0.pop_front().expect("unexpected transfer");
+            assert_eq!(*word, expected, "{:#06X} != {:#06X}", word, expected);
+            *word = response;
+        }
+        Ok(words)
+    }
+}
+
+fn get_bus(words: &[(u16, u16)], cs_toggles: usize) -> MCP25xx<Spi16<Mock16>, MockCS> {
+    MCP25xx::new(
+        Spi16::new(Mock16(words.iter().copied().collect())),
+        MockCS::new(cs_toggles),
+    )
+}
+
+#[test]
+fn test_spi16_write_register() {
+    let mut bus = get_bus(
+        &[
+            (
+                (Instruction::Write as u16) << 8 | CANCTRL::ADDRESS as u16,
+                0,
+            ),
+            // the last byte is written with BitModify
+            (
+                (Instruction::BitModify as u16) << 8 | CANCTRL::ADDRESS as u16,
+                0,
+            ),
+            (0xFF07, 0),
+        ],
+        2,
+    );
+    bus.write_register(CANCTRL::from(0x07)).unwrap();
+    bus.cs.done();
+
+    let mut bus = get_bus(
+        &[
+            ((Instruction::Write as u16) << 8 | CNF3::ADDRESS as u16, 0),
+            (0x0102, 0),
+            (
+                (Instruction::BitModify as u16) << 8 | CNF1::ADDRESS as u16,
+                0,
+            ),
+            (0xFF03, 0),
+        ],
+        2,
+    );
+    bus.write_registers(CNF3::ADDRESS, &[1, 2, 3]).unwrap();
+    bus.cs.done();
+}
+
+#[test]
+fn test_spi16_read() {
+    let mut bus = get_bus(
+        &[
+            ((Instruction::ReadStatus as u16) << 8, 0x00AB),
+            ((Instruction::Read as u16) << 8 | CANSTAT::ADDRESS as u16, 0),
+            (0, 0x80FF),
+            ((Instruction::Rts as u16 | 1) << 8, 0),
+        ],
+        3,
+    );
+    assert_eq!(u8::from(bus.read_status().unwrap()), 0xAB);
+    assert_eq!(u8::from(bus.read_register::<CANSTAT>().unwrap()), 0x80);
+    bus.request_to_send(mcp25xx::TxBuffer::TXB0).unwrap();
+    bus.cs.done();
+}
+
+#[test]
+fn test_spi16_read_ahead() {
+    let mut bus = get_bus(
+        &[
+            ((Instruction::Read as u16) << 8 | 0x60, 0),
+            (0, 0x0102),
+            (0, 0x0304),
+            (0, 0x0506),
+        ],
+        1,
+    );
+    let mut buf = [0; 5];
+    bus.set_cs_low();
+    bus.spi_write(&[Instruction::Read as u8, 0x60]).unwrap();
+    bus.spi_transfer(&mut buf[..3]).unwrap();
+    bus.spi_transfer(&mut buf[3..]).unwrap();
+    bus.set_cs_high();
+    assert_eq!(buf, [1, 2, 3, 4, 5]);
+    bus.cs.done();
+}
@@ -1,10 +1,19 @@
-use embedded_hal_mock::spi::Transaction;
+use embedded_hal_mock::delay::MockNoop;
+use std::convert::Infallible;
+
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::digital::v2::OutputPin;
+use embedded_hal_mock::spi::{Mock, Transaction};
 
 use mcp25xx::registers::*;
-use mcp25xx::{CanFrame, Instruction};
+use mcp25xx::{
+    AcceptanceFilter, CanFrame, Config, ConfigSet, CsFn, Error, ErrorEvent, ErrorState, Event,
+    ExtendedFilter, HotSwapReport, IdHeader, Instruction, InterruptSource, MCP25xx, MixedFilter,
+    RxBfPinMode, RxBuffer, SoftwareFilterSet, StaticConfig, TxBuffer, TxFifo, WakeReason,
+};
 
 use crate::mock_bus::get_mock_bus;
-use embedded_can::{Can, Frame, Id, StandardId};
+use embedded_can::{Can, ExtendedId, Frame, Id, StandardId};
 
 mod mock_bus;
 
@@ -42,9 +51,9 @@ fn test_set_bitrate() {
 
 #[test]
 fn test_transmit() {
-    #[cfg(any(feature = "mcp2515", feature = "mcp25625"))]
+    #[cfg(feature = "fast_instructions")]
     let load_instruction = vec![Instruction::LoadTxBuffer as u8];
-    #[cfg(not(any(feature = "mcp2515", feature = "mcp25625")))]
+    #[cfg(not(feature = "fast_instructions"))]
     let load_instruction = vec![Instruction::Write as u8, 0x31];
 
     let mut bus = get_mock_bus(
@@ -64,3 +73,2922 @@ fn test_transmit() {
     bus.cs.done();
     bus.spi.done();
 }
+
+#[test]
+#[cfg(feature = "fast_instructions")]
+fn test_write_bounded_retry() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0]),
+            Transaction::write(vec![Instruction::Read as u8, CANCTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b1000_0111]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANCTRL::ADDRESS,
+                0b0000_1000,
+                0b0000_1000,
+            ]),
+            Transaction::write(vec![Instruction::LoadTxBuffer as u8]),
+            Transaction::write(vec![0, 32, 0, 0, 3, 1, 2, 3]),
+            // first attempt loses arbitration
+            Transaction::write(vec![Instruction::Rts as u8 | 1]),
+            Transaction::write(vec![Instruction::Read as u8, TXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_1000]),
+            Transaction::write(vec![Instruction::Read as u8, TXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0010_0000]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANINTF::ADDRESS,
+                0b0000_0100,
+                0,
+            ]),
+            // second attempt succeeds
+            Transaction::write(vec![Instruction::Rts as u8 | 1]),
+            Transaction::write(vec![Instruction::Read as u8, TXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANINTF::ADDRESS,
+                0b0000_0100,
+                0,
+            ]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANCTRL::ADDRESS,
+                0b0000_1000,
+                0b0000_0000,
+            ]),
+            // the controller never ends the attempt, e.g. in bus-off
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0]),
+            Transaction::write(vec![Instruction::Read as u8, CANCTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b1000_0111]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANCTRL::ADDRESS,
+                0b0000_1000,
+                0b0000_1000,
+            ]),
+            Transaction::write(vec![Instruction::LoadTxBuffer as u8]),
+            Transaction::write(vec![0, 32, 0, 0, 3, 1, 2, 3]),
+            Transaction::write(vec![Instruction::Rts as u8 | 1]),
+            Transaction::write(vec![Instruction::Read as u8, TXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_1000]),
+            Transaction::write(vec![Instruction::Read as u8, TXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_1000]),
+            Transaction::write(vec![Instruction::Read as u8, TXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_1000]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                TXB0CTRL::ADDRESS,
+                0b0000_1000,
+                0,
+            ]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANCTRL::ADDRESS,
+                0b0000_1000,
+                0b0000_0000,
+            ]),
+        ],
+        22,
+    );
+
+    let frame = CanFrame::new(Id::Standard(StandardId::new(1).unwrap()), &[1, 2, 3]).unwrap();
+
+    let mut delay = RecordingDelay(vec![]);
+    bus.try_write_bounded_retry(&frame, 2, &mut delay, 1000)
+        .unwrap();
+    assert_eq!(delay.0, [10]);
+    assert!(matches!(
+        bus.try_write_bounded_retry(&frame, 2, &mut delay, 15),
+        Err(nb::Error::Other(Error::Timeout))
+    ));
+    assert_eq!(delay.0, [10, 10, 5]);
+    // rejected without touching the controller
+    assert!(matches!(
+        bus.try_write_bounded_retry(&frame, 0, &mut delay, 1000),
+        Err(nb::Error::Other(Error::InvalidArgument))
+    ));
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_cnf_from_parts() {
+    let cnf = CNF::from_parts(
+        CNF1::new().with_sjw(0).with_brp(0),
+        CNF2::new()
+            .with_btlmode(true)
+            .with_sam(false)
+            .with_phseg1(2)
+            .with_prseg(0),
+        CNF3::new().with_wakfil(false).with_phseg2(2),
+    );
+    assert_eq!(cnf.into_bytes(), [0x02, 0x90, 0x00]);
+
+    let table = mcp25xx::bitrates::clock_8mhz::CNF_500K_BPS;
+    assert_eq!(table.cnf1.sjw(), 0);
+    assert_eq!(table.cnf2.phseg1(), 2);
+    assert_eq!(table.cnf3.phseg2(), 2);
+}
+
+#[test]
+fn test_wake_filter() {
+    let config = Config::default()
+        .wake_filter(true)
+        .bitrate(mcp25xx::bitrates::clock_8mhz::CNF_500K_BPS);
+    assert_eq!(config.cnf.into_bytes(), [0xC2, 0x90, 0x00]);
+
+    let config = config.wake_filter(false);
+    assert_eq!(config.cnf.into_bytes(), [0x82, 0x90, 0x00]);
+}
+
+#[test]
+fn test_triple_sampling() {
+    let config = Config::default()
+        .bitrate(mcp25xx::bitrates::clock_16mhz::CNF_83K3_BPS)
+        .triple_sampling(true);
+    assert_eq!(config.cnf.into_bytes(), [0x07, 0xFE, 0x03]);
+
+    let config = config.triple_sampling(false);
+    assert_eq!(config.cnf.into_bytes(), [0x07, 0xBE, 0x03]);
+
+    // kept when the bitrate is chosen afterwards
+    let config = Config::default()
+        .triple_sampling(true)
+        .bitrate(mcp25xx::bitrates::clock_16mhz::CNF_83K3_BPS);
+    assert_eq!(config.cnf.into_bytes(), [0x07, 0xFE, 0x03]);
+    // the table enables triple sampling
+    let config = config
+        .triple_sampling(false)
+        .bitrate(mcp25xx::bitrates::clock_16mhz::CNF_125K_BPS);
+    assert_eq!(u8::from(config.cnf.cnf2), 0xB0);
+    let config = Config::default().bitrate(mcp25xx::bitrates::clock_16mhz::CNF_125K_BPS);
+    assert_eq!(u8::from(config.cnf.cnf2), 0xF0);
+}
+
+#[test]
+fn test_btl_mode() {
+    let config = Config::default()
+        .bitrate(mcp25xx::bitrates::clock_16mhz::CNF_83K3_BPS)
+        .btl_mode(PhSeg2Source::MaxOfPhSeg1AndIpt);
+    assert_eq!(config.cnf.into_bytes(), [0x07, 0x3E, 0x03]);
+
+    let config = config.btl_mode(PhSeg2Source::Cnf3);
+    assert_eq!(config.cnf.into_bytes(), [0x07, 0xBE, 0x03]);
+
+    // kept when the bitrate is chosen afterwards
+    let config = Config::default()
+        .btl_mode(PhSeg2Source::MaxOfPhSeg1AndIpt)
+        .bitrate(mcp25xx::bitrates::clock_16mhz::CNF_83K3_BPS);
+    assert_eq!(config.cnf.into_bytes(), [0x07, 0x3E, 0x03]);
+}
+
+#[test]
+#[cfg(feature = "fast_instructions")]
+fn test_start_of_frame_output() {
+    // clock output disabled
+    let canctrl = CANCTRL::from(0b1000_0011);
+    let config = Config::default()
+        .can_control_register(canctrl)
+        .start_of_frame_output(true)
+        .bitrate(mcp25xx::bitrates::clock_16mhz::CNF_83K3_BPS);
+    assert_eq!(config.cnf.into_bytes(), [0x87, 0xBE, 0x03]);
+    assert_eq!(u8::from(config.canctrl), 0b1000_0111);
+
+    // the table enables the output
+    let config = Config::default().bitrate(mcp25xx::bitrates::clock_16mhz::CNF_125K_BPS);
+    assert_eq!(config.cnf.into_bytes(), [0x86, 0xF0, 0x03]);
+    let config = Config::default()
+        .can_control_register(canctrl)
+        .start_of_frame_output(false)
+        .bitrate(mcp25xx::bitrates::clock_16mhz::CNF_125K_BPS);
+    assert_eq!(config.cnf.into_bytes(), [0x06, 0xF0, 0x03]);
+    assert_eq!(u8::from(config.canctrl), 0b1000_0011);
+}
+
+#[test]
+fn test_config_mode_guard() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_0000]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANCTRL::ADDRESS,
+                0b11100000,
+                0b10000000,
+            ]),
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_0000]),
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b1000_0000]),
+            Transaction::write(vec![Instruction::Write as u8, CNF3::ADDRESS]),
+            Transaction::write(vec![0x82, 0x90, 0x00]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANCTRL::ADDRESS,
+                0b11100000,
+                0b00000000,
+            ]),
+        ],
+        6,
+    );
+
+    let mut config_mode = bus.enter_config_mode().unwrap();
+    assert_eq!(config_mode.previous_mode(), OperationMode::NormalOperation);
+    config_mode
+        .set_bitrate(mcp25xx::bitrates::clock_8mhz::CNF_500K_BPS)
+        .unwrap();
+    drop(config_mode);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_read_rx_buffer_raw() {
+    #[cfg(feature = "fast_instructions")]
+    let (read_instruction, cs_toggles) = (vec![Instruction::ReadRxBuffer as u8 | 2], 1);
+    #[cfg(not(feature = "fast_instructions"))]
+    let (read_instruction, cs_toggles) = (vec![Instruction::Read as u8, 0x71], 2);
+
+    #[cfg(feature = "fast_instructions")]
+    let clear_flag = vec![];
+    #[cfg(not(feature = "fast_instructions"))]
+    let clear_flag = vec![Transaction::write(vec![
+        Instruction::BitModify as u8,
+        CANINTF::ADDRESS,
+        0b10,
+        0,
+    ])];
+
+    let expectations = [
+        vec![
+            Transaction::write(read_instruction),
+            // reserved SIDL bit and DLC above 8 are passed through
+            Transaction::transfer(vec![0; 5], vec![0xAB, 0b1111_0100, 0, 0, 0b0100_1010]),
+            Transaction::transfer(vec![0; 8], vec![1, 2, 3, 4, 5, 6, 7, 8]),
+        ],
+        clear_flag,
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, cs_toggles);
+
+    let mut buf = [0; 13];
+    let len = bus
+        .read_rx_buffer_raw(mcp25xx::RxBuffer::RXB1, &mut buf)
+        .unwrap();
+    assert_eq!(len, 13);
+    assert_eq!(
+        buf,
+        [0xAB, 0b1111_0100, 0, 0, 0b0100_1010, 1, 2, 3, 4, 5, 6, 7, 8]
+    );
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_flush_rx() {
+    let mut bus = get_mock_bus(
+        &[Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANINTF::ADDRESS,
+            0b0000_0011,
+            0,
+        ])],
+        1,
+    );
+    bus.flush_rx().unwrap();
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_adjust_timing() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_0000]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANCTRL::ADDRESS,
+                0b11100000,
+                0b10000000,
+            ]),
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b1000_0000]),
+            Transaction::write(vec![Instruction::Write as u8, CNF1::ADDRESS, 0b1000_0011]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANCTRL::ADDRESS,
+                0b11100000,
+                0b00000000,
+            ]),
+        ],
+        5,
+    );
+
+    assert_eq!(bus.adjust_timing(0, 3), Err(Error::InvalidArgument));
+    assert_eq!(bus.adjust_timing(1, 64), Err(Error::InvalidArgument));
+    bus.adjust_timing(3, 3).unwrap();
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_next_interrupt() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, CANINTF::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b1010_0101]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANINTF::ADDRESS,
+                0b0010_0000,
+                0,
+            ]),
+            Transaction::write(vec![Instruction::Read as u8, CANINTF::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b1000_0101]),
+            Transaction::write(vec![Instruction::Read as u8, CANINTF::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0]),
+        ],
+        4,
+    );
+
+    assert_eq!(bus.next_interrupt().unwrap(), Some(InterruptSource::Error));
+    bus.clear_interrupt(InterruptSource::Error).unwrap();
+    assert_eq!(bus.next_interrupt().unwrap(), Some(InterruptSource::TXB0));
+    assert_eq!(bus.next_interrupt().unwrap(), None);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_standby() {
+    let canstat = |opmod: u8| {
+        [
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![opmod << 5]),
+        ]
+    };
+    let set_mode = |reqop: u8| {
+        Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANCTRL::ADDRESS,
+            0b11100000,
+            reqop << 5,
+        ])
+    };
+    let expectations = [
+        // Listen-Only mode
+        &canstat(0b011)[..],
+        &[set_mode(0b100)],
+        &canstat(0b100),
+        &[set_mode(0b011)],
+        &canstat(0b011),
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, 5);
+
+    bus.standby().unwrap();
+    // nothing to do while in standby
+    bus.standby().unwrap();
+    bus.resume().unwrap();
+    assert_eq!(bus.resume(), Err(Error::InvalidArgument));
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_init() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Reset as u8]),
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_0000]),
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b1000_0000]),
+            Transaction::write(vec![Instruction::Write as u8, CNF3::ADDRESS]),
+            Transaction::write(vec![0x07, 0xBE, 0x03]),
+            Transaction::write(vec![Instruction::Write as u8, RXB0CTRL::ADDRESS, 0]),
+            Transaction::write(vec![Instruction::Write as u8, RXB1CTRL::ADDRESS, 0]),
+            Transaction::write(vec![
+                Instruction::Write as u8,
+                CANCTRL::ADDRESS,
+                0b0000_0111,
+            ]),
+        ],
+        7,
+    );
+
+    let config = Config::default()
+        .mode(OperationMode::NormalOperation)
+        .bitrate(mcp25xx::bitrates::clock_16mhz::CNF_83K3_BPS);
+    bus.init(&config, &mut MockNoop::new()).unwrap();
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_bitrate_check() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, CNF3::ADDRESS]),
+            Transaction::transfer(vec![0; 3], vec![0; 3]),
+            Transaction::write(vec![Instruction::Read as u8, CNF3::ADDRESS]),
+            Transaction::transfer(vec![0; 3], vec![0x07, 0xBE, 0x03]),
+        ],
+        2,
+    );
+    // rejected without any transfer
+    let config = Config::default().mode(OperationMode::NormalOperation);
+    assert!(!config.has_bitrate());
+    assert_eq!(
+        bus.init(&config, &mut MockNoop::new()),
+        Err(Error::InvalidArgument)
+    );
+    assert!(!bus.is_bitrate_set().unwrap());
+    assert!(bus.is_bitrate_set().unwrap());
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_apply_config_staged() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Reset as u8]),
+            Transaction::write(vec![Instruction::Write as u8, CNF3::ADDRESS]),
+            Transaction::write(vec![0x07, 0xBE, 0x03]),
+            Transaction::write(vec![Instruction::Write as u8, RXB0CTRL::ADDRESS, 0]),
+            Transaction::write(vec![Instruction::Write as u8, RXB1CTRL::ADDRESS, 0]),
+            // clock output bits applied, REQOP stays at Configuration
+            Transaction::write(vec![
+                Instruction::Write as u8,
+                CANCTRL::ADDRESS,
+                0b1000_0111,
+            ]),
+        ],
+        5,
+    );
+
+    let config = Config::default()
+        .mode(OperationMode::NormalOperation)
+        .bitrate(mcp25xx::bitrates::clock_16mhz::CNF_83K3_BPS);
+    bus.apply_config_staged(&config).unwrap();
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_config_set() {
+    use mcp25xx::bitrates::clock_16mhz::{CNF_500K_BPS, CNF_83K3_BPS};
+
+    let apply = |cnf: [u8; 3]| {
+        vec![
+            Transaction::write(vec![Instruction::Reset as u8]),
+            Transaction::write(vec![Instruction::Write as u8, CNF3::ADDRESS]),
+            Transaction::write(cnf.to_vec()),
+            Transaction::write(vec![Instruction::Write as u8, RXB0CTRL::ADDRESS, 0]),
+            Transaction::write(vec![Instruction::Write as u8, RXB1CTRL::ADDRESS, 0]),
+            Transaction::write(vec![
+                Instruction::Write as u8,
+                CANCTRL::ADDRESS,
+                0b1000_0111,
+            ]),
+        ]
+    };
+    let expectations = [apply([0x07, 0xBE, 0x03]), apply([0x86, 0xF0, 0x00])].concat();
+    let mut bus = get_mock_bus(&expectations, 10);
+
+    let mut configs = ConfigSet::new([
+        StaticConfig {
+            cnf: CNF_83K3_BPS,
+            ..StaticConfig::new([])
+        },
+        StaticConfig {
+            cnf: CNF_500K_BPS,
+            ..StaticConfig::new([])
+        },
+    ]);
+    configs.switch_config(&mut bus, 0).unwrap();
+    // already applied
+    configs.switch_config(&mut bus, 0).unwrap();
+    configs.switch_config(&mut bus, 1).unwrap();
+    assert_eq!(configs.current(), Some(1));
+    assert_eq!(
+        configs.switch_config(&mut bus, 2),
+        Err(Error::InvalidArgument)
+    );
+    let mut unset: ConfigSet<1, 0> = ConfigSet::new([StaticConfig::new([])]);
+    assert_eq!(
+        unset.switch_config(&mut bus, 0),
+        Err(Error::InvalidArgument)
+    );
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_accept_ids() {
+    let config_mode = [
+        Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+        Transaction::transfer(vec![0], vec![0b0000_0000]),
+        Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANCTRL::ADDRESS,
+            0b11100000,
+            0b10000000,
+        ]),
+        Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+        Transaction::transfer(vec![0], vec![0b1000_0000]),
+    ];
+    let filters = [
+        Transaction::write(vec![Instruction::Write as u8, 0x20]),
+        Transaction::write(vec![0xFF, 0xC0, 0, 0]),
+        Transaction::write(vec![Instruction::Write as u8, 0x00]),
+        Transaction::write(vec![0x20, 0x00, 0, 0]),
+        Transaction::write(vec![Instruction::Write as u8, 0x04]),
+        Transaction::write(vec![0x20, 0x40, 0, 0]),
+        Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANCTRL::ADDRESS,
+            0b11100000,
+            0b00000000,
+        ]),
+    ];
+    let mut bus = get_mock_bus(&[&config_mode[..], &filters[..]].concat(), 7);
+
+    let ids = [0x100, 0x101, 0x102].map(|id| Id::Standard(StandardId::new(id).unwrap()));
+    // three ids do not fit into the two filters of RXB0
+    assert!(!bus.accept_ids(mcp25xx::RxBuffer::RXB0, &ids).unwrap());
+
+    let mixed = [ids[0], Id::Extended(ExtendedId::new(0x100).unwrap())];
+    assert_eq!(
+        bus.accept_ids(mcp25xx::RxBuffer::RXB0, &mixed),
+        Err(Error::InvalidArgument)
+    );
+    assert_eq!(
+        bus.accept_ids(mcp25xx::RxBuffer::RXB0, &[]),
+        Err(Error::InvalidArgument)
+    );
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_free_tx_buffers() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0b1111_1111]),
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0b0001_0100]),
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0b1010_1011]),
+        ],
+        3,
+    );
+    assert_eq!(bus.free_tx_buffers().unwrap(), 0);
+    assert_eq!(bus.free_tx_buffers().unwrap(), 1);
+    assert_eq!(bus.free_tx_buffers().unwrap(), 3);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_read_rx_buffer_into() {
+    #[cfg(feature = "fast_instructions")]
+    let (read_instruction, cs_toggles) = (vec![Instruction::ReadRxBuffer as u8], 1);
+    #[cfg(not(feature = "fast_instructions"))]
+    let (read_instruction, cs_toggles) = (vec![Instruction::Read as u8, 0x61], 2);
+
+    #[cfg(feature = "fast_instructions")]
+    let clear_flag = vec![];
+    #[cfg(not(feature = "fast_instructions"))]
+    let clear_flag = vec![Transaction::write(vec![
+        Instruction::BitModify as u8,
+        CANINTF::ADDRESS,
+        0b01,
+        0,
+    ])];
+
+    let expectations = [
+        vec![
+            Transaction::write(read_instruction),
+            Transaction::transfer(vec![0, 32, 0, 0, 8], vec![0, 64, 0, 0, 2]),
+            Transaction::transfer(vec![1, 2], vec![5, 6]),
+        ],
+        clear_flag,
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, cs_toggles);
+
+    let mut frame = CanFrame::new(StandardId::new(1).unwrap(), &[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+    bus.read_rx_buffer_into(mcp25xx::RxBuffer::RXB0, &mut frame)
+        .unwrap();
+    assert_eq!(frame.id(), Id::Standard(StandardId::new(2).unwrap()));
+    assert_eq!(frame.data(), &[5, 6]);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_on_interrupt() {
+    #[cfg(feature = "fast_instructions")]
+    let (read_instruction, clear_flag, cs_toggles) =
+        (vec![Instruction::ReadRxBuffer as u8], vec![], 5);
+    #[cfg(not(feature = "fast_instructions"))]
+    let (read_instruction, clear_flag, cs_toggles) = (
+        vec![Instruction::Read as u8, 0x61],
+        vec![Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANINTF::ADDRESS,
+            0b01,
+            0,
+        ])],
+        6,
+    );
+    let expectations = [
+        vec![
+            Transaction::write(vec![Instruction::Read as u8, CANINTF::ADDRESS]),
+            // RXB0, TXB1, Error and Message Error
+            Transaction::transfer(vec![0], vec![0b1010_1001]),
+            Transaction::write(read_instruction),
+            Transaction::transfer(vec![0; 5], vec![0x24, 0x60, 0, 0, 1]),
+            Transaction::transfer(vec![0], vec![0x55]),
+        ],
+        clear_flag,
+        vec![
+            Transaction::write(vec![Instruction::Read as u8, EFLG::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0100_0000]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                EFLG::ADDRESS,
+                0b1100_0000,
+                0,
+            ]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANINTF::ADDRESS,
+                0b1010_1000,
+                0,
+            ]),
+        ],
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, cs_toggles);
+
+    let mut events = vec![];
+    bus.on_interrupt(|event| events.push(event)).unwrap();
+    assert_eq!(events.len(), 4);
+    assert!(matches!(&events[0], Event::Received(frame) if frame.data() == [0x55]));
+    assert!(matches!(events[1], Event::TransmitComplete(TxBuffer::TXB1)));
+    assert!(matches!(events[2], Event::Error(eflg) if eflg.rx0ovr()));
+    assert!(matches!(events[3], Event::MessageError));
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_interrupt_code() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b1000_1100]),
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b1000_0000]),
+        ],
+        2,
+    );
+    let code = bus.interrupt_code().unwrap();
+    assert_eq!(code, InterruptFlagCode::RXB0Interrupt);
+    assert_eq!(code.source(), Some(InterruptSource::RXB0));
+    assert_eq!(bus.interrupt_code().unwrap().source(), None);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_on_wake() {
+    let expectations = [
+        Transaction::write(vec![Instruction::Read as u8, CANINTF::ADDRESS]),
+        Transaction::transfer(vec![0], vec![0b0100_0001]),
+        Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANINTF::ADDRESS,
+            0b0100_0000,
+            0,
+        ]),
+        Transaction::write(vec![Instruction::Read as u8, CANINTF::ADDRESS]),
+        Transaction::transfer(vec![0], vec![0b0000_0001]),
+    ];
+    let mut bus = get_mock_bus(&expectations, 3);
+
+    assert_eq!(bus.on_wake().unwrap(), WakeReason::BusActivity);
+    assert_eq!(bus.on_wake().unwrap(), WakeReason::Command);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_poll_error_state() {
+    let read_eflg = |eflg| {
+        [
+            Transaction::write(vec![Instruction::Read as u8, EFLG::ADDRESS]),
+            Transaction::transfer(vec![0], vec![eflg]),
+        ]
+    };
+    let expectations = [
+        read_eflg(0b0000_0000),
+        read_eflg(0b0000_0011),
+        read_eflg(0b0001_0111),
+        read_eflg(0b0001_0111),
+        read_eflg(0b0010_0111),
+        read_eflg(0b0000_0000),
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, 6);
+
+    let event = |previous, current| Some(ErrorEvent { previous, current });
+    assert_eq!(bus.poll_error_state().unwrap(), None);
+    assert_eq!(
+        bus.poll_error_state().unwrap(),
+        event(ErrorState::Active, ErrorState::Warning)
+    );
+    assert_eq!(
+        bus.poll_error_state().unwrap(),
+        event(ErrorState::Warning, ErrorState::Passive)
+    );
+    assert_eq!(bus.poll_error_state().unwrap(), None);
+    assert_eq!(
+        bus.poll_error_state().unwrap(),
+        event(ErrorState::Passive, ErrorState::BusOff)
+    );
+    assert_eq!(
+        bus.poll_error_state().unwrap(),
+        event(ErrorState::BusOff, ErrorState::Active)
+    );
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_recover() {
+    let expectations = [
+        Transaction::write(vec![Instruction::Read as u8, EFLG::ADDRESS]),
+        Transaction::transfer(vec![0], vec![0b0010_0111]),
+        Transaction::write(vec![
+            Instruction::BitModify as u8,
+            EFLG::ADDRESS,
+            0b1100_0000,
+            0,
+        ]),
+        Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANINTF::ADDRESS,
+            0b1010_0000,
+            0,
+        ]),
+        Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANCTRL::ADDRESS,
+            0b11100000,
+            0b00000000,
+        ]),
+        Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+        Transaction::transfer(vec![0], vec![0b0000_0000]),
+        Transaction::write(vec![Instruction::Read as u8, EFLG::ADDRESS]),
+        Transaction::transfer(vec![0], vec![0b0000_0000]),
+    ];
+    let mut bus = get_mock_bus(&expectations, 6);
+
+    assert!(bus.poll_error_state().unwrap().is_some());
+    bus.recover(OperationMode::NormalOperation).unwrap();
+    // the tracker starts over from the active state
+    assert_eq!(bus.poll_error_state().unwrap(), None);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_active_high_cs() {
+    struct RecordingPin(Vec<bool>);
+
+    impl OutputPin for RecordingPin {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.0.push(false);
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.0.push(true);
+            Ok(())
+        }
+    }
+
+    let spi = Mock::new(&[Transaction::write(vec![Instruction::Reset as u8])]);
+    let mut bus = MCP25xx::new_active_high_cs(spi, RecordingPin(vec![]));
+    bus.reset().unwrap();
+    assert_eq!(bus.cs.0 .0, [true, false]);
+    bus.spi.done();
+}
+
+#[test]
+fn test_external_cs() {
+    let spi = Mock::new(&[Transaction::write(vec![Instruction::Reset as u8])]);
+    let mut bus = MCP25xx::new_external_cs(spi);
+    bus.reset().unwrap();
+    bus.spi.done();
+
+    let mut selects = vec![];
+    let spi = Mock::new(&[Transaction::write(vec![Instruction::Reset as u8])]);
+    let mut bus = MCP25xx::new(spi, CsFn(|select| selects.push(select)));
+    bus.reset().unwrap();
+    bus.spi.done();
+    drop(bus);
+    assert_eq!(selects, [true, false]);
+}
+
+#[test]
+fn test_extended_filter() {
+    let first = ExtendedId::new(0x18FE_F100).unwrap();
+    let last = ExtendedId::new(0x18FE_F1FF).unwrap();
+    let filter = ExtendedFilter::range(first, last);
+    assert_eq!(filter.mask_bits(), 0x1FFF_FF00);
+    assert!(filter.matches(Id::Extended(ExtendedId::new(0x18FE_F142).unwrap())));
+    assert!(!filter.matches(Id::Extended(ExtendedId::new(0x18FE_F242).unwrap())));
+    assert!(!filter.matches(Id::Standard(StandardId::new(0x100).unwrap())));
+
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Write as u8, 0x20]),
+            // EXIDE set in SIDL
+            Transaction::write(vec![0xFF, 0xEB, 0xFF, 0x00]),
+            Transaction::write(vec![Instruction::Write as u8, 0x00]),
+            Transaction::write(vec![0xC7, 0xEA, 0xF1, 0x00]),
+        ],
+        4,
+    );
+    bus.set_filter(AcceptanceFilter::Mask0, filter.mask())
+        .unwrap();
+    bus.set_filter(AcceptanceFilter::Filter0, filter.filter())
+        .unwrap();
+    bus.spi.done();
+
+    let single = ExtendedFilter::range(first, first);
+    assert_eq!(single.mask_bits(), 0x1FFF_FFFF);
+    let all = ExtendedFilter::range(ExtendedId::ZERO, ExtendedId::MAX);
+    assert_eq!(all.mask_bits(), 0);
+}
+
+#[test]
+fn test_register_address() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, CANINTF::ADDRESS]),
+            Transaction::transfer(vec![0; 2], vec![0b01, 0b10]),
+            Transaction::write(vec![Instruction::Write as u8, 0x46]),
+            Transaction::write(vec![0x12, 0x34]),
+        ],
+        2,
+    );
+    let mut flags = [0; 2];
+    bus.read_registers_from(RegisterAddress::CANINTF, &mut flags)
+        .unwrap();
+    assert_eq!(flags, [0b01, 0b10]);
+    bus.write_registers_to(RegisterAddress::TXB1D0, &[0x12, 0x34])
+        .unwrap();
+    // same addresses as the typed registers
+    assert_eq!(u8::from(RegisterAddress::EFLG), EFLG::ADDRESS);
+    assert_eq!(u8::from(RegisterAddress::TXRTSCTRL), TXRTSCTRL::ADDRESS);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_apply_register_image() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Write as u8, CNF3::ADDRESS]),
+            Transaction::write(vec![0x86]),
+            Transaction::write(vec![0xF0]),
+            Transaction::write(vec![0x00]),
+            Transaction::write(vec![Instruction::Write as u8, CANCTRL::ADDRESS]),
+            Transaction::write(vec![0x00]),
+            Transaction::write(vec![Instruction::Write as u8, CANINTE::ADDRESS]),
+            Transaction::write(vec![0x03]),
+        ],
+        3,
+    );
+    bus.apply_register_image(&[
+        (CNF3::ADDRESS, 0x86),
+        (CNF2::ADDRESS, 0xF0),
+        (CNF1::ADDRESS, 0x00),
+        (CANCTRL::ADDRESS, 0x00),
+        (CANINTE::ADDRESS, 0x03),
+    ])
+    .unwrap();
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_register_map() {
+    let map: Vec<_> = register_map().collect();
+    assert_eq!(map.len(), 0x80);
+    assert!(map.iter().zip(0..).all(|(&(address, _), i)| address == i));
+    for (address, name) in [
+        (CANSTAT::ADDRESS, "CANSTAT"),
+        (CANCTRL::ADDRESS, "CANCTRL"),
+        (EFLG::ADDRESS, "EFLG"),
+        (TXB0CTRL::ADDRESS, "TXB0CTRL"),
+        (TXRTSCTRL::ADDRESS, "TXRTSCTRL"),
+        (0x1E, "CANSTAT"),
+        (0x6D, "RXB0D7"),
+    ] {
+        assert_eq!(map[address as usize].1, name);
+    }
+}
+
+#[test]
+fn test_dump_registers() {
+    let registers: Vec<u8> = (0..0x80).collect();
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, 0x00]),
+            Transaction::transfer(vec![0; 0x80], registers.clone()),
+        ],
+        1,
+    );
+    let mut out = [0; 0x80];
+    bus.dump_registers(&mut out).unwrap();
+    assert_eq!(out[..], registers[..]);
+    bus.spi.done();
+}
+
+#[cfg(feature = "fast_instructions")]
+#[test]
+fn test_data_byte_filter() {
+    let bytes = |header: IdHeader| [header.sidh(), header.sidl(), header.eid8(), header.eid0()];
+    let id = StandardId::new(0x123).unwrap();
+
+    let filter = mcp25xx::DataByteFilter::new(id, 0x7FF, [0xFE, 0xF1], [0xFF, 0xFF]);
+    // SID10..SID3 in SIDH, SID2..SID0 in SIDL, EXIDE cleared
+    assert_eq!(bytes(filter.filter()), [0x24, 0x60, 0xFE, 0xF1]);
+    assert_eq!(bytes(filter.mask()), [0xFF, 0xE0, 0xFF, 0xFF]);
+
+    // bits outside the masks are cleared in the filter
+    let filter = mcp25xx::DataByteFilter::new(id, 0x7F8, [0xFE, 0xF1], [0xF0, 0x00]);
+    assert_eq!(bytes(filter.filter()), [0x24, 0x00, 0xF0, 0x00]);
+    assert_eq!(bytes(filter.mask()), [0xFF, 0x00, 0xF0, 0x00]);
+
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Write as u8, 0x24]),
+            Transaction::write(vec![0xFF, 0x00, 0xF0, 0x00]),
+            Transaction::write(vec![Instruction::Write as u8, 0x10]),
+            Transaction::write(vec![0x24, 0x00, 0xF0, 0x00]),
+        ],
+        4,
+    );
+    bus.set_filter(AcceptanceFilter::Mask1, filter.mask())
+        .unwrap();
+    bus.set_filter(AcceptanceFilter::Filter3, filter.filter())
+        .unwrap();
+    bus.spi.done();
+}
+
+#[cfg(not(feature = "fast_instructions"))]
+#[test]
+fn test_receive_frame_type_only() {
+    let rxm = |config: Config| -> (u8, u8) { (config.rxb0ctrl.into(), config.rxb1ctrl.into()) };
+
+    assert_eq!(rxm(Config::default().receive_standard_only()), (0x20, 0x20));
+    assert_eq!(rxm(Config::default().receive_extended_only()), (0x40, 0x40));
+
+    // other bits are kept
+    let config = Config::default()
+        .receive_buffer_0(RXB0CTRL::default().with_bukt(true))
+        .receive_extended_only();
+    assert_eq!(rxm(config), (0x44, 0x40));
+}
+
+#[test]
+fn test_filter_plan() {
+    use mcp25xx::{FilterIssue, FilterPlan};
+    use AcceptanceFilter::*;
+
+    let id = |id| IdHeader::from(StandardId::new(id).unwrap());
+    let mask = IdHeader::from(StandardId::MAX);
+    let issues = |filters: &[(AcceptanceFilter, IdHeader)]| -> Vec<FilterIssue> {
+        let config = Config::default().filters(filters);
+        FilterPlan::new(&config).issues().collect()
+    };
+
+    let complete = [
+        (Mask0, mask),
+        (Filter0, id(0x100)),
+        (Filter1, id(0x101)),
+        (Mask1, mask),
+        (Filter2, id(0x200)),
+        (Filter3, id(0x201)),
+        (Filter4, id(0x202)),
+        (Filter5, id(0x203)),
+    ];
+    let config = Config::default().filters(&complete);
+    assert_eq!(FilterPlan::new(&config).check(), Ok(()));
+    assert_eq!(issues(&[]), []);
+
+    assert_eq!(
+        issues(&[(Filter0, id(0x100)), (Filter1, id(0x101))]),
+        [FilterIssue::MissingMask(Mask0)]
+    );
+    assert_eq!(
+        issues(&[(Mask0, mask), (Filter0, id(0x100))]),
+        [FilterIssue::UnsetFilter(Filter1)]
+    );
+    assert_eq!(
+        issues(&[
+            (Mask0, mask),
+            (Filter0, id(0x100)),
+            (Filter1, id(0x101)),
+            (Filter1, id(0x102)),
+        ]),
+        [FilterIssue::Overwritten(Filter1)]
+    );
+
+    // a Mask1 ignoring the low bits makes Filter2 to Filter5 equal
+    let mut filters = complete;
+    filters[3].1 = IdHeader::from(StandardId::new(0x7F0).unwrap());
+    assert_eq!(
+        issues(&filters),
+        [
+            FilterIssue::Redundant(Filter3),
+            FilterIssue::Redundant(Filter4),
+            FilterIssue::Redundant(Filter5)
+        ]
+    );
+
+    // an extended Filter2 never overlaps with the standard filters
+    filters[4].1 = IdHeader::from(ExtendedId::new(0x200 << 18).unwrap());
+    assert_eq!(
+        issues(&filters),
+        [
+            FilterIssue::Redundant(Filter4),
+            FilterIssue::Redundant(Filter5)
+        ]
+    );
+
+    assert_eq!(Filter1.receive_buffer(), RxBuffer::RXB0);
+    assert_eq!(Filter2.receive_buffer(), RxBuffer::RXB1);
+    assert_eq!(Mask1.receive_buffer(), RxBuffer::RXB1);
+}
+
+#[test]
+fn test_j1939_filter() {
+    use mcp25xx::J1939Filter;
+
+    let ext = |id| Id::Extended(ExtendedId::new(id).unwrap());
+
+    // EEC1, PDU2 format
+    let eec1 = J1939Filter::pgn(61444);
+    assert_eq!(eec1.extended_filter().mask_bits(), 0x03FF_FF00);
+    assert!(eec1.matches(ext(0x0CF0_0400)));
+    assert!(eec1.matches(ext(0x18F0_0417)));
+    assert!(!eec1.matches(ext(0x0CF0_0500)));
+    assert!(!eec1.matches(Id::Standard(StandardId::new(0x400).unwrap())));
+
+    let bytes = |header: IdHeader| [header.sidh(), header.sidl(), header.eid8(), header.eid0()];
+    assert_eq!(bytes(eec1.filter()), [0x07, 0x88, 0x04, 0x00]);
+    assert_eq!(bytes(eec1.mask()), [0x1F, 0xEB, 0xFF, 0x00]);
+
+    let from_engine = eec1.priority(3).source(0x00);
+    assert!(from_engine.matches(ext(0x0CF0_0400)));
+    assert!(!from_engine.matches(ext(0x18F0_0400)));
+    assert!(!from_engine.matches(ext(0x0CF0_0417)));
+    // no destination address in PDU2
+    assert_eq!(from_engine.destination(0x21), from_engine);
+
+    // Request, PDU1 format with destination address
+    let request = J1939Filter::pgn(59904);
+    assert_eq!(request.extended_filter().mask_bits(), 0x03FF_0000);
+    assert!(request.matches(ext(0x18EA_FF00)));
+    assert!(request.matches(ext(0x18EA_2117)));
+    let to_node = request.destination(0x21);
+    assert!(to_node.matches(ext(0x18EA_2117)));
+    assert!(!to_node.matches(ext(0x18EA_FF00)));
+}
+
+#[test]
+fn test_mixed_filter() {
+    let filter = MixedFilter::new(
+        StandardId::new(0x105).unwrap(),
+        0x7F0,
+        ExtendedId::new(0x18FE_F100).unwrap(),
+        0x1FFF_FF00,
+    );
+    assert!(filter.matches(Id::Standard(StandardId::new(0x10A).unwrap())));
+    assert!(!filter.matches(Id::Standard(StandardId::new(0x110).unwrap())));
+    assert!(filter.matches(Id::Extended(ExtendedId::new(0x18FE_F142).unwrap())));
+    // the same raw value as an extended id does not pass the standard filter
+    assert!(!filter.matches(Id::Extended(ExtendedId::new(0x100).unwrap())));
+
+    let bytes = |header: IdHeader| [header.sidh(), header.sidl(), header.eid8(), header.eid0()];
+    let filters = filter.filters();
+    assert!(matches!(filters[0].0, AcceptanceFilter::Mask0));
+    // no EID bits, data bytes of standard frames are not compared
+    assert_eq!(bytes(filters[0].1), [0xFE, 0x00, 0x00, 0x00]);
+    for (filter, header) in &filters[1..3] {
+        assert!(matches!(
+            filter,
+            AcceptanceFilter::Filter0 | AcceptanceFilter::Filter1
+        ));
+        // EXIDE cleared
+        assert_eq!(bytes(*header), [0x20, 0x00, 0x00, 0x00]);
+    }
+    assert!(matches!(filters[3].0, AcceptanceFilter::Mask1));
+    assert_eq!(bytes(filters[3].1), [0xFF, 0xEB, 0xFF, 0x00]);
+    for (filter, header) in &filters[4..] {
+        assert!(matches!(
+            filter,
+            AcceptanceFilter::Filter2
+                | AcceptanceFilter::Filter3
+                | AcceptanceFilter::Filter4
+                | AcceptanceFilter::Filter5
+        ));
+        // EXIDE set
+        assert_eq!(bytes(*header), [0xC7, 0xEA, 0xF1, 0x00]);
+    }
+}
+
+#[test]
+fn test_transmit_round_robin() {
+    #[cfg(feature = "fast_instructions")]
+    let load_instruction = |buf: u8| vec![Instruction::LoadTxBuffer as u8 | (buf * 2)];
+    #[cfg(not(feature = "fast_instructions"))]
+    let load_instruction = |buf: u8| vec![Instruction::Write as u8, 0x31 + 0x10 * buf];
+
+    // all buffers stay free, so only the cursor decides
+    let transmit = |buf: u8| {
+        [
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0]),
+            Transaction::write(load_instruction(buf)),
+            Transaction::write(vec![0, 32, 0, 0, 1, buf]),
+            Transaction::write(vec![Instruction::Rts as u8 | (1 << buf)]),
+        ]
+    };
+    let expectations = [transmit(0), transmit(1), transmit(2), transmit(0)].concat();
+    let mut bus = get_mock_bus(&expectations, 12).with_tx_round_robin(true);
+
+    for data in [0, 1, 2, 0] {
+        let frame = CanFrame::new(StandardId::new(1).unwrap(), &[data]).unwrap();
+        bus.try_transmit(&frame).unwrap();
+    }
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_set_tx_priorities() {
+    let modify = |address, priority| {
+        Transaction::write(vec![
+            Instruction::BitModify as u8,
+            address,
+            0b0000_0011,
+            priority,
+        ])
+    };
+    let mut bus = get_mock_bus(
+        &[
+            modify(TXB0CTRL::ADDRESS, 0b01),
+            modify(TXB1CTRL::ADDRESS, 0b00),
+            modify(TXB2CTRL::ADDRESS, 0b11),
+        ],
+        3,
+    );
+    bus.set_tx_priorities(
+        mcp25xx::TxPriority::Low,
+        mcp25xx::TxPriority::Lowest,
+        mcp25xx::TxPriority::Highest,
+    )
+    .unwrap();
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_read_tx_buffer() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, 0x41]),
+            Transaction::transfer(
+                vec![0; 13],
+                vec![0x24, 0x60, 0, 0, 2, 0xAA, 0xBB, 0xCC, 0, 0, 0, 0, 0],
+            ),
+            // power-up garbage with DLC 15
+            Transaction::write(vec![Instruction::Read as u8, 0x51]),
+            Transaction::transfer(vec![0; 13], vec![0, 0, 0, 0, 0x0F, 1, 2, 3, 4, 5, 6, 7, 8]),
+        ],
+        2,
+    );
+    let frame = bus.read_tx_buffer(TxBuffer::TXB1).unwrap();
+    assert_eq!(frame.id(), Id::Standard(StandardId::new(0x123).unwrap()));
+    assert_eq!(frame.data(), &[0xAA, 0xBB]);
+    let frame = bus.read_tx_buffer(TxBuffer::TXB2).unwrap();
+    assert!(frame.is_valid());
+    assert_eq!(frame.dlc(), 8);
+    assert_eq!(frame.data(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+    assert!(format!("{:?}", frame).contains("data: [1, 2, 3, 4, 5, 6, 7, 8]"));
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_last_tx_acked() {
+    let read = |address, value| {
+        [
+            Transaction::write(vec![Instruction::Read as u8, address]),
+            Transaction::transfer(vec![0], vec![value]),
+        ]
+    };
+    let mut bus = get_mock_bus(
+        &[
+            // no acknowledgement, the request is still retried
+            read(0x40, 0b0001_1000),
+            read(0x50, 0b0000_1000),
+            read(0x30, 0),
+            read(0x2C, 0b0000_0100),
+            read(0x30, 0),
+            read(0x2C, 0b0001_1000),
+            read(0x50, 0b0100_0000),
+        ]
+        .concat(),
+        7,
+    );
+    assert!(!bus.last_tx_acked(TxBuffer::TXB1).unwrap());
+    assert_eq!(bus.last_tx_acked(TxBuffer::TXB2), Err(Error::Busy));
+    assert!(bus.last_tx_acked(TxBuffer::TXB0).unwrap());
+    assert!(!bus.last_tx_acked(TxBuffer::TXB0).unwrap());
+    assert!(!bus.last_tx_acked(TxBuffer::TXB2).unwrap());
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_is_tx_pending() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0b0001_0000]),
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0b0001_0000]),
+        ],
+        2,
+    );
+    assert!(bus.is_tx_pending(TxBuffer::TXB1).unwrap());
+    assert!(!bus.is_tx_pending(TxBuffer::TXB2).unwrap());
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_tx_fifo() {
+    #[cfg(feature = "fast_instructions")]
+    let (load_txb1, load_txb0) = (
+        vec![Instruction::LoadTxBuffer as u8 | 2],
+        vec![Instruction::LoadTxBuffer as u8],
+    );
+    #[cfg(not(feature = "fast_instructions"))]
+    let (load_txb1, load_txb0) = (
+        vec![Instruction::Write as u8, 0x41],
+        vec![Instruction::Write as u8, 0x31],
+    );
+
+    let mut bus = get_mock_bus(
+        &[
+            // TXB2 busy
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0b0100_0000]),
+            Transaction::write(load_txb1),
+            Transaction::write(vec![0, 1 << 5, 0, 0, 0]),
+            Transaction::write(vec![Instruction::Rts as u8 | 0b010]),
+            Transaction::write(load_txb0),
+            Transaction::write(vec![0, 2 << 5, 0, 0, 1, 9]),
+            Transaction::write(vec![Instruction::Rts as u8 | 0b001]),
+        ],
+        5,
+    );
+    let mut fifo: TxFifo<3> = TxFifo::new();
+    fifo.push(CanFrame::new(StandardId::new(3).unwrap(), &[]).unwrap())
+        .unwrap();
+    fifo.push(CanFrame::new(StandardId::new(1).unwrap(), &[]).unwrap())
+        .unwrap();
+    fifo.push(CanFrame::new(StandardId::new(2).unwrap(), &[9]).unwrap())
+        .unwrap();
+    assert!(fifo.is_full());
+    assert!(fifo
+        .push(CanFrame::new(StandardId::new(4).unwrap(), &[]).unwrap())
+        .is_err());
+
+    assert_eq!(fifo.service(&mut bus).unwrap(), 2);
+    assert_eq!(fifo.len(), 1);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_transmit_on() {
+    #[cfg(feature = "fast_instructions")]
+    let load_instruction = vec![Instruction::LoadTxBuffer as u8 | 4];
+    #[cfg(not(feature = "fast_instructions"))]
+    let load_instruction = vec![Instruction::Write as u8, 0x51];
+
+    let mut bus = get_mock_bus(
+        &[
+            // TXB0 busy
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0b0000_0100]),
+            Transaction::write(load_instruction),
+            Transaction::write(vec![0, 32, 0, 0, 1, 7]),
+            Transaction::write(vec![Instruction::Rts as u8 | 0b100]),
+            // TXB0 and TXB2 busy, TXB1 is free but not allowed
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0b0100_0100]),
+        ],
+        4,
+    );
+    let frame = CanFrame::new(StandardId::new(1).unwrap(), &[7]).unwrap();
+    let allowed = [TxBuffer::TXB0, TxBuffer::TXB2];
+    assert!(matches!(
+        bus.try_transmit_on(&frame, &allowed),
+        Ok(TxBuffer::TXB2)
+    ));
+    assert!(matches!(
+        bus.try_transmit_on(&frame, &allowed),
+        Err(nb::Error::WouldBlock)
+    ));
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_transmit_preemptive() {
+    #[cfg(feature = "fast_instructions")]
+    let load_instruction = vec![Instruction::LoadTxBuffer as u8 | 4];
+    #[cfg(not(feature = "fast_instructions"))]
+    let load_instruction = vec![Instruction::Write as u8, 0x51];
+
+    let pending = [
+        Transaction::write(vec![Instruction::ReadStatus as u8]),
+        Transaction::transfer(vec![0], vec![0b0101_0100]),
+        // TXB0 with highest TXP and id 0x100, TXB1 and TXB2 with lowest TXP and ids 0x200, 0x300
+        Transaction::write(vec![Instruction::Read as u8, 0x30]),
+        Transaction::transfer(vec![0; 6], vec![0b0000_1011, 0x20, 0, 0, 0, 1]),
+        Transaction::write(vec![Instruction::Read as u8, 0x40]),
+        Transaction::transfer(vec![0; 6], vec![0b0000_1000, 0x40, 0, 0, 0, 1]),
+        Transaction::write(vec![Instruction::Read as u8, 0x50]),
+        Transaction::transfer(vec![0; 6], vec![0b0000_1000, 0x60, 0, 0, 0, 1]),
+    ];
+    let expectations = [
+        &pending[..],
+        &[
+            Transaction::write(vec![Instruction::BitModify as u8, 0x50, 0b0000_1000, 0]),
+            Transaction::write(vec![Instruction::Read as u8, 0x50]),
+            Transaction::transfer(vec![0], vec![0b0100_0000]),
+            Transaction::write(load_instruction),
+            Transaction::write(vec![0x0A, 0, 0, 0, 1, 7]),
+            Transaction::write(vec![Instruction::Rts as u8 | 0b100]),
+        ],
+        &pending[..],
+        // TXB2 is being transmitted, the abort only takes effect afterwards
+        &pending[..],
+        &[
+            Transaction::write(vec![Instruction::BitModify as u8, 0x50, 0b0000_1000, 0]),
+            Transaction::write(vec![Instruction::Read as u8, 0x50]),
+            Transaction::transfer(vec![0], vec![0b0000_1000]),
+        ],
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, 4 + 4 + 4 + 6);
+
+    let urgent = CanFrame::new(StandardId::new(0x50).unwrap(), &[7]).unwrap();
+    assert!(matches!(
+        bus.try_transmit_preemptive(&urgent),
+        Ok(Some(Id::Standard(id))) if id.as_raw() == 0x300
+    ));
+    let unimportant = CanFrame::new(StandardId::new(0x400).unwrap(), &[7]).unwrap();
+    assert!(matches!(
+        bus.try_transmit_preemptive(&unimportant),
+        Err(nb::Error::WouldBlock)
+    ));
+    assert!(matches!(
+        bus.try_transmit_preemptive(&urgent),
+        Err(nb::Error::WouldBlock)
+    ));
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+#[cfg(not(feature = "fast_instructions"))]
+fn test_receive_keeps_flag_of_other_buffer() {
+    use embedded_hal::blocking::spi::{Transfer, Write};
+
+    /// Emulates CANINTF, a frame arrives in RXB1 right after the status was read
+    #[derive(Default)]
+    struct CanintfSim {
+        canintf: u8,
+        status_requested: bool,
+    }
+
+    impl Write<u8> for CanintfSim {
+        type Error = Infallible;
+
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            const READ_STATUS: u8 = Instruction::ReadStatus as u8;
+            const BIT_MODIFY: u8 = Instruction::BitModify as u8;
+            match *words {
+                [READ_STATUS] => self.status_requested = true,
+                [BIT_MODIFY, CANINTF::ADDRESS, mask, value] => {
+                    self.canintf = (self.canintf & !mask) | (value & mask)
+                }
+                _ => {}
+            }
+            Ok(())
+        }
+    }
+
+    impl Transfer<u8> for CanintfSim {
+        type Error = Infallible;
+
+        fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+            words.fill(0);
+            if core::mem::take(&mut self.status_requested) {
+                words[0] = self.canintf;
+                self.canintf |= 0b10;
+            }
+            Ok(words)
+        }
+    }
+
+    let spi = CanintfSim {
+        canintf: 0b01,
+        ..Default::default()
+    };
+    let mut bus = MCP25xx::new(spi, NoCs);
+    bus.try_receive().unwrap();
+    // only the flag of RXB0 is cleared
+    assert_eq!(bus.spi.canintf, 0b10);
+}
+
+#[test]
+#[cfg(feature = "fast_instructions")]
+fn test_pending_rx() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::RxStatus as u8]),
+            // RXB0 full, extended data frame, filter 1 matched
+            Transaction::transfer(vec![0], vec![0b0101_0001]),
+            Transaction::write(vec![Instruction::RxStatus as u8]),
+            // both full, standard remote frame, filter 0 matched through rollover
+            Transaction::transfer(vec![0], vec![0b1100_1110]),
+        ],
+        2,
+    );
+
+    let pending = bus.pending_rx().unwrap();
+    assert_eq!(pending.count(), 1);
+    assert!(pending.contains(RxBuffer::RXB0));
+    assert!(!pending.contains(RxBuffer::RXB1));
+    assert_eq!(
+        pending.message_type(RxBuffer::RXB0),
+        Some(MessageType::ExtendedData)
+    );
+    assert_eq!(pending.message_type(RxBuffer::RXB1), None);
+
+    let pending = bus.pending_rx().unwrap();
+    assert_eq!(pending.count(), 2);
+    assert_eq!(pending.message_type(RxBuffer::RXB0), None);
+    assert_eq!(
+        pending.message_type(RxBuffer::RXB1),
+        Some(MessageType::StandardRemote)
+    );
+
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+#[cfg(feature = "fast_instructions")]
+fn test_rx_status() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::RxStatus as u8]),
+            // RXB1 full, extended remote frame, filter 4 matched
+            Transaction::transfer(vec![0], vec![0b1001_1100]),
+            Transaction::write(vec![Instruction::RxStatus as u8]),
+            // both full, standard data frame, filter 1 matched through rollover
+            Transaction::transfer(vec![0], vec![0b1100_0111]),
+            Transaction::write(vec![Instruction::RxStatus as u8]),
+            Transaction::transfer(vec![0], vec![0b0000_1000]),
+        ],
+        3,
+    );
+
+    let status = bus.rx_status().unwrap();
+    assert_eq!(status.received_buffer(), Some(ReceivedBuffers::RXB1));
+    assert_eq!(status.message_type(), MessageType::ExtendedRemote);
+    assert_eq!(status.filter_match(), FilterMatch::RXF4);
+
+    let status = bus.rx_status().unwrap();
+    assert_eq!(status.received_buffer(), Some(ReceivedBuffers::Both));
+    assert_eq!(status.message_type(), MessageType::StandardData);
+    assert_eq!(status.filter_match(), FilterMatch::RXF1Rollover);
+
+    let status = bus.rx_status().unwrap();
+    assert_eq!(status.received_buffer(), None);
+    assert_eq!(status.message_type(), MessageType::StandardRemote);
+
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_rx_bf_pin() {
+    let modify = |mask: u8, value: u8| {
+        Transaction::write(vec![
+            Instruction::BitModify as u8,
+            BFPCTRL::ADDRESS,
+            mask,
+            value,
+        ])
+    };
+    let mut bus = get_mock_bus(
+        &[
+            modify(0b0000_0101, 0b0000_0000),
+            modify(0b0000_0101, 0b0000_0101),
+            modify(0b0000_0101, 0b0000_0100),
+            modify(0b0000_1010, 0b0000_0000),
+            modify(0b0000_1010, 0b0000_1010),
+            modify(0b0000_1010, 0b0000_1000),
+            modify(0b0001_0000, 0b0000_0000),
+            modify(0b0001_0000, 0b0001_0000),
+            modify(0b0010_0000, 0b0000_0000),
+            modify(0b0010_0000, 0b0010_0000),
+            Transaction::write(vec![Instruction::Read as u8, BFPCTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0010_1000]),
+            Transaction::write(vec![Instruction::Read as u8, BFPCTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0010_1000]),
+            Transaction::write(vec![Instruction::Read as u8, BFPCTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_0101]),
+        ],
+        13,
+    );
+
+    for pin in [RxBuffer::RXB0, RxBuffer::RXB1] {
+        for mode in [
+            RxBfPinMode::Disabled,
+            RxBfPinMode::Interrupt,
+            RxBfPinMode::DigitalOutput,
+        ] {
+            bus.rx_bf_pin_mode(pin, mode).unwrap();
+        }
+    }
+    for pin in [RxBuffer::RXB0, RxBuffer::RXB1] {
+        for state in [false, true] {
+            bus.set_rx_bf_pin(pin, state).unwrap();
+        }
+    }
+
+    assert_eq!(
+        bus.rx_bf_pin(RxBuffer::RXB0).unwrap(),
+        (RxBfPinMode::Disabled, false)
+    );
+    assert_eq!(
+        bus.rx_bf_pin(RxBuffer::RXB1).unwrap(),
+        (RxBfPinMode::DigitalOutput, true)
+    );
+    assert_eq!(
+        bus.rx_bf_pin(RxBuffer::RXB0).unwrap(),
+        (RxBfPinMode::Interrupt, false)
+    );
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_static_config_conversion() {
+    let filters = [
+        (
+            AcceptanceFilter::Filter0,
+            StandardId::new(1).unwrap().into(),
+        ),
+        (AcceptanceFilter::Mask0, StandardId::MAX.into()),
+    ];
+    let config = Config::default()
+        .mode(OperationMode::Loopback)
+        .filters(&filters);
+
+    let static_config = StaticConfig::<2>::try_from(&config).unwrap();
+    assert_eq!(static_config.canctrl.reqop(), OperationMode::Loopback);
+    assert!(StaticConfig::<1>::try_from(&config).is_err());
+
+    let config = Config::from(&static_config);
+    assert_eq!(config.filters.len(), 2);
+    assert_eq!(
+        config.filters[0].1.id(),
+        Id::Standard(StandardId::new(1).unwrap())
+    );
+}
+
+#[test]
+fn test_set_promiscuous() {
+    let modify = |address: u8, value: u8| {
+        Transaction::write(vec![
+            Instruction::BitModify as u8,
+            address,
+            0b0110_0000,
+            value,
+        ])
+    };
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, RXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_0100]),
+            Transaction::write(vec![Instruction::Read as u8, RXB1CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_0001]),
+            modify(RXB0CTRL::ADDRESS, 0b0110_0000),
+            modify(RXB1CTRL::ADDRESS, 0b0110_0000),
+            // enabling twice keeps the saved modes
+            modify(RXB0CTRL::ADDRESS, 0b0000_0000),
+            modify(RXB1CTRL::ADDRESS, 0b0000_0000),
+            Transaction::write(vec![Instruction::Read as u8, RXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0100_0000]),
+            Transaction::write(vec![Instruction::Read as u8, RXB1CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0100_0000]),
+            modify(RXB0CTRL::ADDRESS, 0b0110_0000),
+            modify(RXB1CTRL::ADDRESS, 0b0110_0000),
+            Transaction::write(vec![Instruction::Reset as u8]),
+            // the reset cleared the receive buffer modes, enabling saves them again
+            Transaction::write(vec![Instruction::Read as u8, RXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_0000]),
+            Transaction::write(vec![Instruction::Read as u8, RXB1CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_0000]),
+            modify(RXB0CTRL::ADDRESS, 0b0110_0000),
+            modify(RXB1CTRL::ADDRESS, 0b0110_0000),
+            Transaction::write(vec![Instruction::Reset as u8]),
+        ],
+        16,
+    );
+
+    bus.set_promiscuous(true).unwrap();
+    bus.set_promiscuous(true).unwrap();
+    bus.set_promiscuous(false).unwrap();
+    // already disabled
+    bus.set_promiscuous(false).unwrap();
+
+    bus.set_promiscuous(true).unwrap();
+    bus.reset().unwrap();
+    bus.set_promiscuous(true).unwrap();
+    bus.reset().unwrap();
+    // the modes saved before the reset are not restored
+    bus.set_promiscuous(false).unwrap();
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_software_filter_set() {
+    let mut filters: SoftwareFilterSet<2> = SoftwareFilterSet::new();
+    assert!(!filters.matches(StandardId::ZERO.into()));
+    filters.add(StandardId::new(0x120).unwrap(), 0x7F0).unwrap();
+    filters
+        .add(ExtendedId::new(0x120).unwrap(), 0x1FFF_FFFF)
+        .unwrap();
+    assert!(filters.add(StandardId::ZERO, 0).is_err());
+    assert_eq!(filters.len(), 2);
+
+    assert!(filters.matches(StandardId::new(0x12F).unwrap().into()));
+    assert!(!filters.matches(StandardId::new(0x130).unwrap().into()));
+    assert!(filters.matches(ExtendedId::new(0x120).unwrap().into()));
+    // the standard filter does not match extended frames
+    assert!(!filters.matches(ExtendedId::new(0x121).unwrap().into()));
+
+    filters.clear();
+    assert!(filters.is_empty());
+    assert!(!filters.matches(StandardId::new(0x120).unwrap().into()));
+}
+
+#[test]
+fn test_receive_filtered() {
+    #[cfg(feature = "fast_instructions")]
+    let read = |buf: u8, id: u8| {
+        vec![
+            Transaction::write(vec![Instruction::ReadRxBuffer as u8 | (buf * 2)]),
+            Transaction::transfer(vec![0; 5], vec![id, 0, 0, 0, 0]),
+            Transaction::transfer(vec![], vec![]),
+        ]
+    };
+    #[cfg(not(feature = "fast_instructions"))]
+    let read = |buf: u8, id: u8| {
+        vec![
+            Transaction::write(vec![Instruction::Read as u8, 0x61 + 0x10 * buf]),
+            Transaction::transfer(vec![0; 5], vec![id, 0, 0, 0, 0]),
+            Transaction::transfer(vec![], vec![]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANINTF::ADDRESS,
+                1 << buf,
+                0,
+            ]),
+        ]
+    };
+    #[cfg(feature = "fast_instructions")]
+    let cs_toggles = 6;
+    #[cfg(not(feature = "fast_instructions"))]
+    let cs_toggles = 9;
+
+    let status = |flags: u8| {
+        vec![
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![flags]),
+        ]
+    };
+    let expectations = [
+        // RXB0 is rejected, RXB1 accepted
+        status(0b11),
+        read(0, 1),
+        read(1, 2),
+        // RXB0 is rejected and nothing else is pending
+        status(0b01),
+        read(0, 1),
+        status(0b00),
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, cs_toggles);
+
+    let accept = |frame: &CanFrame| frame.id() == Id::Standard(StandardId::new(2 << 3).unwrap());
+    let frame = bus.receive_filtered(accept).unwrap();
+    assert_eq!(frame.id(), Id::Standard(StandardId::new(2 << 3).unwrap()));
+    assert!(matches!(
+        bus.receive_filtered(accept),
+        Err(nb::Error::WouldBlock)
+    ));
+    assert!(matches!(
+        bus.receive_filtered(accept),
+        Err(nb::Error::WouldBlock)
+    ));
+    bus.cs.done();
+    bus.spi.done();
+}
+
+struct NoCs;
+
+impl OutputPin for NoCs {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+struct RecordingDelay(Vec<u32>);
+
+impl DelayUs<u32> for RecordingDelay {
+    fn delay_us(&mut self, us: u32) {
+        self.0.push(us);
+    }
+}
+
+#[test]
+fn test_reset_with_delay() {
+    let mut bus = get_mock_bus(&[Transaction::write(vec![Instruction::Reset as u8])], 1);
+    let mut delay = RecordingDelay(vec![]);
+    bus.reset_with_delay(&mut delay).unwrap();
+    assert_eq!(delay.0, [mcp25xx::RESET_DELAY_US]);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_receive_with_timeout() {
+    let poll = [
+        Transaction::write(vec![Instruction::ReadStatus as u8]),
+        Transaction::transfer(vec![0], vec![0]),
+    ];
+    let mut bus = get_mock_bus(
+        &[poll.clone(), poll.clone(), poll.clone(), poll].concat(),
+        4,
+    );
+    let mut delay = RecordingDelay(vec![]);
+    assert_eq!(
+        bus.receive_with_timeout(&mut delay, 250).unwrap_err(),
+        Error::Timeout
+    );
+    assert_eq!(delay.0, [100, 100, 50]);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_send_blocking() {
+    #[cfg(feature = "fast_instructions")]
+    let load_instruction = vec![Instruction::LoadTxBuffer as u8];
+    #[cfg(not(feature = "fast_instructions"))]
+    let load_instruction = vec![Instruction::Write as u8, 0x31];
+
+    let send = [
+        Transaction::write(vec![Instruction::ReadStatus as u8]),
+        Transaction::transfer(vec![0], vec![0]),
+        Transaction::write(load_instruction),
+        Transaction::write(vec![0, 32, 0, 0, 1, 7]),
+        Transaction::write(vec![Instruction::Rts as u8 | 1]),
+        Transaction::write(vec![Instruction::Read as u8, TXB0CTRL::ADDRESS]),
+    ];
+    let expectations = [
+        send.to_vec(),
+        vec![
+            Transaction::transfer(vec![0], vec![0b0000_1000]),
+            Transaction::write(vec![Instruction::Read as u8, TXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANINTF::ADDRESS,
+                0b0000_0100,
+                0,
+            ]),
+        ],
+        // bus error, the request gets aborted
+        send.to_vec(),
+        vec![
+            Transaction::transfer(vec![0], vec![0b0001_1000]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                TXB0CTRL::ADDRESS,
+                0b0000_1000,
+                0,
+            ]),
+        ],
+        // lost arbitration, then sent by the automatic retransmission
+        send.to_vec(),
+        vec![
+            Transaction::transfer(vec![0], vec![0b0010_1000]),
+            Transaction::write(vec![Instruction::Read as u8, TXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0010_0000]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANINTF::ADDRESS,
+                0b0000_0100,
+                0,
+            ]),
+        ],
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, 17);
+    let mut delay = RecordingDelay(vec![]);
+    let frame = CanFrame::new(StandardId::new(1).unwrap(), &[7]).unwrap();
+
+    bus.send_blocking(&frame, &mut delay, 1000).unwrap();
+    assert_eq!(delay.0, [10]);
+    assert_eq!(
+        bus.send_blocking(&frame, &mut delay, 1000).unwrap_err(),
+        Error::TransmitFailed
+    );
+    bus.send_blocking(&frame, &mut delay, 1000).unwrap();
+    assert_eq!(bus.retransmit_count(), 1);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+#[cfg(feature = "fast_instructions")]
+fn test_one_shot_send() {
+    use mcp25xx::OneShotResult;
+
+    let osm = |set: bool| {
+        Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANCTRL::ADDRESS,
+            0b0000_1000,
+            if set { 0b0000_1000 } else { 0 },
+        ])
+    };
+    let send = |canctrl: u8| {
+        vec![
+            Transaction::write(vec![Instruction::Read as u8, CANCTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![canctrl]),
+            osm(true),
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0]),
+            Transaction::write(vec![Instruction::LoadTxBuffer as u8]),
+            Transaction::write(vec![0, 32, 0, 0, 1, 7]),
+            Transaction::write(vec![Instruction::Rts as u8 | 1]),
+        ]
+    };
+    let read_ctrl = |ctrl: u8| {
+        [
+            Transaction::write(vec![Instruction::Read as u8, TXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![ctrl]),
+        ]
+    };
+    let expectations = [
+        send(0b1000_0111),
+        read_ctrl(0b0000_1000).to_vec(),
+        // lost arbitration, not retried
+        read_ctrl(0b0010_0000).to_vec(),
+        vec![
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANINTF::ADDRESS,
+                0b0000_0100,
+                0,
+            ]),
+            osm(false),
+        ],
+        // One-Shot mode was enabled before, the attempt never ends
+        send(0b1000_1111),
+        read_ctrl(0b0000_1000).to_vec(),
+        read_ctrl(0b0000_1000).to_vec(),
+        read_ctrl(0b0000_1000).to_vec(),
+        vec![
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                TXB0CTRL::ADDRESS,
+                0b0000_1000,
+                0,
+            ]),
+            osm(true),
+        ],
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, 19);
+    let mut delay = RecordingDelay(vec![]);
+    let frame = CanFrame::new(StandardId::new(1).unwrap(), &[7]).unwrap();
+
+    assert_eq!(
+        bus.one_shot_send(&frame, &mut delay, 1000).unwrap(),
+        OneShotResult::ArbitrationLost
+    );
+    assert_eq!(delay.0, [10]);
+    assert_eq!(
+        bus.one_shot_send(&frame, &mut delay, 15).unwrap(),
+        OneShotResult::Timeout
+    );
+    assert_eq!(delay.0, [10, 10, 5]);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_request_data() {
+    #[cfg(feature = "fast_instructions")]
+    let (load_instruction, read_instruction, cs_toggles) = (
+        vec![Instruction::LoadTxBuffer as u8],
+        vec![Instruction::ReadRxBuffer as u8],
+        9,
+    );
+    #[cfg(not(feature = "fast_instructions"))]
+    let (load_instruction, read_instruction, cs_toggles) = (
+        vec![Instruction::Write as u8, 0x31],
+        vec![Instruction::Read as u8, 0x61],
+        11,
+    );
+    #[cfg(feature = "fast_instructions")]
+    let clear_flag = vec![];
+    #[cfg(not(feature = "fast_instructions"))]
+    let clear_flag = vec![Transaction::write(vec![
+        Instruction::BitModify as u8,
+        CANINTF::ADDRESS,
+        0b01,
+        0,
+    ])];
+
+    let receive = |sidl: u8| {
+        [
+            vec![
+                Transaction::write(vec![Instruction::ReadStatus as u8]),
+                Transaction::transfer(vec![0], vec![0b01]),
+                Transaction::write(read_instruction.clone()),
+                Transaction::transfer(vec![0; 5], vec![0, sidl, 0, 0, 1]),
+                Transaction::transfer(vec![0], vec![0xAB]),
+            ],
+            clear_flag.clone(),
+        ]
+        .concat()
+    };
+    let expectations = [
+        vec![
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0]),
+            Transaction::write(load_instruction),
+            // remote frame requesting 2 bytes
+            Transaction::write(vec![0, 1 << 5, 0, 0, 0x40 | 2]),
+            Transaction::write(vec![Instruction::Rts as u8 | 1]),
+            Transaction::write(vec![Instruction::Read as u8, TXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANINTF::ADDRESS,
+                0b0000_0100,
+                0,
+            ]),
+        ],
+        // another id, dropped
+        receive(2 << 5),
+        receive(1 << 5),
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, cs_toggles);
+    let mut delay = RecordingDelay(vec![]);
+
+    let response = bus
+        .request_data(StandardId::new(1).unwrap(), 2, &mut delay, 1000)
+        .unwrap();
+    assert_eq!(response.data(), &[0xAB]);
+    assert_eq!(delay.0, [100]);
+    assert_eq!(
+        bus.request_data(StandardId::new(1).unwrap(), 9, &mut delay, 1000)
+            .unwrap_err(),
+        Error::InvalidArgument
+    );
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_estimate_bus_load() {
+    #[cfg(feature = "fast_instructions")]
+    let (read_instruction, cs_toggles) = (vec![Instruction::ReadRxBuffer as u8], 13);
+    #[cfg(not(feature = "fast_instructions"))]
+    let (read_instruction, cs_toggles) = (vec![Instruction::Read as u8, 0x61], 14);
+    #[cfg(feature = "fast_instructions")]
+    let clear_flag = vec![];
+    #[cfg(not(feature = "fast_instructions"))]
+    let clear_flag = vec![Transaction::write(vec![
+        Instruction::BitModify as u8,
+        CANINTF::ADDRESS,
+        0b01,
+        0,
+    ])];
+
+    let status = |flags: u8| {
+        vec![
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![flags]),
+        ]
+    };
+    let expectations = [
+        status(0b01),
+        vec![
+            Transaction::write(read_instruction),
+            Transaction::transfer(vec![0; 5], vec![0, 0x20, 0, 0, 8]),
+            Transaction::transfer(vec![0; 8], vec![0; 8]),
+        ],
+        clear_flag,
+        // once before and after each of the 10 delays
+        (0..11).flat_map(|_| status(0)).collect(),
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, cs_toggles);
+    let mut delay = RecordingDelay(vec![]);
+
+    // a standard frame with 8 bytes takes 135 us at 1 Mbps
+    assert_eq!(bus.estimate_bus_load(&mut delay, 1, 1_000_000).unwrap(), 13);
+    assert_eq!(delay.0, [100; 10]);
+    assert_eq!(
+        bus.estimate_bus_load(&mut delay, 0, 1_000_000).unwrap_err(),
+        Error::InvalidArgument
+    );
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_tx_rts_inputs() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_0000]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANCTRL::ADDRESS,
+                0b11100000,
+                0b10000000,
+            ]),
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b1000_0000]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                TXRTSCTRL::ADDRESS,
+                0b0000_0111,
+                0b0000_0010,
+            ]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANCTRL::ADDRESS,
+                0b11100000,
+                0b00000000,
+            ]),
+            Transaction::write(vec![Instruction::Read as u8, TXRTSCTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0010_1010]),
+        ],
+        6,
+    );
+    let mut config_mode = bus.enter_config_mode().unwrap();
+    config_mode.set_tx_rts_pins([false, true, false]).unwrap();
+    drop(config_mode);
+    assert_eq!(bus.read_tx_rts_inputs().unwrap(), [true, false, true]);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_short_rx_transfer() {
+    use embedded_hal::blocking::spi::{Transfer, Write};
+
+    /// Delivers a frame with DLC 4, but only two of its data bytes
+    struct ShortSpi;
+
+    impl Write<u8> for ShortSpi {
+        type Error = Infallible;
+
+        fn write(&mut self, _words: &[u8]) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl Transfer<u8> for ShortSpi {
+        type Error = Infallible;
+
+        fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+            match words.len() {
+                5 => {
+                    words.copy_from_slice(&[0x20, 0, 0, 0, 4]);
+                    Ok(words)
+                }
+                _ => {
+                    words[..2].copy_from_slice(&[1, 2]);
+                    Ok(&words[..2])
+                }
+            }
+        }
+    }
+
+    let mut bus = MCP25xx::new(ShortSpi, NoCs);
+    let frame = bus.read_rx_buffer(RxBuffer::RXB0).unwrap();
+    assert!(bus.last_rx_truncated());
+    assert!(frame.looks_truncated());
+    assert_eq!(frame.data(), [1, 2, 0, 0]);
+}
+
+#[test]
+fn test_read_bitrate_info() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, CNF3::ADDRESS]),
+            Transaction::transfer(vec![0; 3], vec![0x07, 0xBE, 0x03]),
+            Transaction::write(vec![Instruction::Read as u8, CNF3::ADDRESS]),
+            // BTLMODE cleared, Phase Segment 2 follows Phase Segment 1
+            Transaction::transfer(vec![0; 3], vec![0x00, 0x3F, 0x00]),
+        ],
+        2,
+    );
+    assert_eq!(
+        bus.read_bitrate_info(16_000_000).unwrap(),
+        mcp25xx::bitrates::TimingInfo {
+            bitrate: 83_333,
+            sample_point_permille: 666,
+            time_quanta: 24,
+            sjw: 1,
+        }
+    );
+    let timing = bus.read_bitrate_info(16_000_000).unwrap();
+    assert_eq!(timing.time_quanta, 1 + 8 + 8 + 8);
+    assert_eq!(timing.bitrate, 320_000);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_hot_swap_filters() {
+    let mode_change = |mode: u8| {
+        [
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_0000]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANCTRL::ADDRESS,
+                0b11100000,
+                0b10000000,
+            ]),
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![mode]),
+        ]
+    };
+    let restore = Transaction::write(vec![
+        Instruction::BitModify as u8,
+        CANCTRL::ADDRESS,
+        0b11100000,
+        0b00000000,
+    ]);
+    let canstat = |mode: u8| {
+        [
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![mode]),
+        ]
+    };
+    let expectations = [
+        &mode_change(0b1000_0000)[..],
+        &[
+            Transaction::write(vec![Instruction::Write as u8, 0x20]),
+            Transaction::write(vec![0xFF, 0xE0, 0, 0]),
+            Transaction::write(vec![Instruction::Write as u8, 0x00]),
+            Transaction::write(vec![0x20, 0x00, 0, 0]),
+            restore.clone(),
+        ],
+        // the previous mode is confirmed after one poll
+        &canstat(0b1000_0000)[..],
+        &canstat(0b0000_0000)[..],
+        // only Filter0 changed
+        &mode_change(0b1000_0000)[..],
+        &[
+            Transaction::write(vec![Instruction::Write as u8, 0x00]),
+            Transaction::write(vec![0x40, 0x00, 0, 0]),
+            restore,
+        ],
+        &canstat(0b0000_0000)[..],
+        // reset forgets the cache
+        &[Transaction::write(vec![Instruction::Reset as u8])],
+        &mode_change(0b0000_0000)[..],
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, 18);
+    let mut delay = RecordingDelay(Vec::new());
+
+    let filters = |id| {
+        [
+            (AcceptanceFilter::Mask0, IdHeader::from(StandardId::MAX)),
+            (
+                AcceptanceFilter::Filter0,
+                IdHeader::from(StandardId::new(id).unwrap()),
+            ),
+        ]
+    };
+    let report = bus.hot_swap_filters(&filters(0x100), &mut delay).unwrap();
+    assert_eq!(
+        (report.written, report.off_bus_bytes, report.mode_wait_us),
+        (2, 16, 10)
+    );
+    assert_eq!(delay.0, [10]);
+    // nothing changed, no mode change
+    let report = bus.hot_swap_filters(&filters(0x100), &mut delay).unwrap();
+    assert_eq!(report, HotSwapReport::default());
+    let report = bus.hot_swap_filters(&filters(0x200), &mut delay).unwrap();
+    assert_eq!(
+        (report.written, report.off_bus_bytes, report.mode_wait_us),
+        (1, 10, 0)
+    );
+
+    bus.reset().unwrap();
+    // the controller does not reach Configuration mode within the poll limit
+    bus.set_mode_poll_limit(1);
+    assert_eq!(
+        bus.hot_swap_filters(&filters(0x200), &mut delay),
+        Err(Error::ModeChangeTimeout)
+    );
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_spi_retries() {
+    use embedded_hal::blocking::spi::{Transfer, Write};
+
+    /// Fails the first `failures` transfers, reads 0x42 afterwards
+    struct FlakySpi {
+        failures: usize,
+        writes: Vec<Vec<u8>>,
+    }
+
+    impl Write<u8> for FlakySpi {
+        type Error = ();
+
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            self.writes.push(words.to_vec());
+            Ok(())
+        }
+    }
+
+    impl Transfer<u8> for FlakySpi {
+        type Error = ();
+
+        fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(());
+            }
+            words.fill(0x42);
+            Ok(words)
+        }
+    }
+
+    let spi = FlakySpi {
+        failures: 2,
+        writes: vec![],
+    };
+    let mut bus = MCP25xx::new(spi, NoCs);
+    // no retries by default
+    assert!(bus.read_register::<TEC>().is_err());
+
+    let mut bus = bus.with_spi_retries(1);
+    assert_eq!(bus.read_register::<TEC>(), Ok(TEC(0x42)));
+    assert_eq!(bus.spi.writes.len(), 3);
+
+    // reading a receive buffer clears its flag, so it is not repeated
+    bus.spi.failures = 1;
+    assert!(bus.read_rx_buffer(RxBuffer::RXB0).is_err());
+}
+
+#[test]
+fn test_read_filter() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, 0x24]),
+            Transaction::transfer(vec![0; 4], vec![0x91, 0xA8, 0x56, 0x78]),
+            Transaction::write(vec![Instruction::Read as u8, 0x00]),
+            // EXIDE cleared
+            Transaction::transfer(vec![0; 4], vec![0x91, 0xA0, 0x56, 0x78]),
+        ],
+        2,
+    );
+
+    let filter = bus.read_filter(AcceptanceFilter::Mask1).unwrap();
+    assert_eq!(
+        filter.id(),
+        Id::Extended(ExtendedId::new(0x1234_5678).unwrap())
+    );
+    let filter = bus.read_filter(AcceptanceFilter::Filter0).unwrap();
+    assert_eq!(filter.id(), Id::Standard(StandardId::new(0x48D).unwrap()));
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_transmit_remote_frame() {
+    #[cfg(feature = "fast_instructions")]
+    let load_instruction = vec![Instruction::LoadTxBuffer as u8];
+    #[cfg(not(feature = "fast_instructions"))]
+    let load_instruction = vec![Instruction::Write as u8, 0x31];
+
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(load_instruction),
+            // RTR bit and DLC of 8, but no data bytes
+            Transaction::write(vec![0, 32, 0, 0, 0b0100_1000]),
+        ],
+        1,
+    );
+
+    let frame = CanFrame::new_remote(StandardId::new(1).unwrap(), 8).unwrap();
+    bus.load_tx_buffer(mcp25xx::TxBuffer::TXB0, &frame).unwrap();
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+#[cfg(feature = "fast_instructions")]
+fn test_probe_fast_instructions() {
+    let control = |txb0ctrl: u8| {
+        [
+            Transaction::write(vec![Instruction::Read as u8, TXB0CTRL::ADDRESS]),
+            Transaction::transfer(vec![0], vec![txb0ctrl]),
+        ]
+    };
+    let round_trip = |pattern: [u8; 2], read_back: [u8; 2]| {
+        [
+            Transaction::write(vec![Instruction::LoadTxBuffer as u8 | 1]),
+            Transaction::write(pattern.to_vec()),
+            Transaction::write(vec![Instruction::Read as u8, 0x36]),
+            Transaction::transfer(vec![0; 2], read_back.to_vec()),
+        ]
+    };
+    let expectations = [
+        &control(0)[..],
+        &round_trip([0xA5, 0x5A], [0xA5, 0x5A]),
+        &round_trip([0x5A, 0xA5], [0x5A, 0xA5]),
+        // the MCP2510 ignores LoadTxBuffer
+        &control(0),
+        &round_trip([0xA5, 0x5A], [0x00, 0x00]),
+        // TXREQ set
+        &control(0b0000_1000),
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, 9);
+
+    assert_eq!(bus.probe_fast_instructions(), Ok(true));
+    assert_eq!(bus.probe_fast_instructions(), Ok(false));
+    assert_eq!(bus.probe_fast_instructions(), Err(Error::Busy));
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_spi_integrity() {
+    let control = [
+        Transaction::write(vec![Instruction::Read as u8, TXB0CTRL::ADDRESS]),
+        Transaction::transfer(vec![0], vec![0]),
+    ];
+    let round_trip = |pattern: u8, read_back: u8| {
+        [
+            Transaction::write(vec![Instruction::Write as u8, 0x36]),
+            Transaction::write(vec![pattern]),
+            Transaction::write(vec![Instruction::Read as u8, 0x36]),
+            Transaction::transfer(vec![0], vec![read_back]),
+        ]
+    };
+    let patterns = [
+        0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0xFE, 0xFD, 0xFB, 0xF7, 0xEF, 0xDF, 0xBF,
+        0x7F,
+    ];
+    let expectations = [
+        control.to_vec(),
+        patterns.iter().flat_map(|&p| round_trip(p, p)).collect(),
+        // bit 3 stuck high
+        control.to_vec(),
+        round_trip(0x01, 0x09).to_vec(),
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, 36);
+
+    assert_eq!(bus.spi_integrity_test(), Ok(true));
+    assert_eq!(bus.spi_integrity_test(), Ok(false));
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_poll_transmit_complete() {
+    let control = |txb1ctrl: u8| {
+        [
+            Transaction::write(vec![Instruction::Read as u8, 0x40]),
+            Transaction::transfer(vec![0], vec![txb1ctrl]),
+        ]
+    };
+    let clear = || {
+        Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANINTF::ADDRESS,
+            0b0000_1000,
+            0,
+        ])
+    };
+    let expectations = [
+        // TXREQ still set
+        &control(0b0000_1000)[..],
+        &control(0b0000_0000),
+        &[clear()],
+        // TXERR set
+        &control(0b0001_0000),
+        &[clear()],
+        // MLOA left over from an attempt before the successful retransmission
+        &control(0b0010_0000),
+        &[clear()],
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, 7);
+
+    let buf = mcp25xx::TxBuffer::TXB1;
+    assert!(matches!(
+        bus.poll_transmit_complete(buf),
+        Err(nb::Error::WouldBlock)
+    ));
+    bus.poll_transmit_complete(buf).unwrap();
+    assert!(matches!(
+        bus.poll_transmit_complete(buf),
+        Err(nb::Error::Other(Error::TransmitFailed))
+    ));
+    bus.poll_transmit_complete(buf).unwrap();
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_retransmit_count() {
+    let control = |txb1ctrl: u8| {
+        [
+            Transaction::write(vec![Instruction::Read as u8, 0x40]),
+            Transaction::transfer(vec![0], vec![txb1ctrl]),
+        ]
+    };
+    let expectations = [
+        // lost arbitration, retrying
+        &control(0b0010_1000)[..],
+        &control(0b0010_1000),
+        &control(0b0000_0000),
+        &[Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANINTF::ADDRESS,
+            0b0000_1000,
+            0,
+        ])],
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, 4);
+
+    let buf = mcp25xx::TxBuffer::TXB1;
+    while let Err(nb::Error::WouldBlock) = bus.poll_transmit_complete(buf) {
+        assert_eq!(bus.retransmit_count(), 0);
+    }
+    assert_eq!(bus.retransmit_count(), 1);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_transmit_tracked() {
+    #[cfg(feature = "fast_instructions")]
+    let load_instruction = vec![Instruction::LoadTxBuffer as u8 | 4];
+    #[cfg(not(feature = "fast_instructions"))]
+    let load_instruction = vec![Instruction::Write as u8, 0x51];
+
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            // TXB0 and TXB1 pending
+            Transaction::transfer(vec![0], vec![0b0001_0100]),
+            Transaction::write(load_instruction),
+            Transaction::write(vec![0, 32, 0, 0, 1, 7]),
+            Transaction::write(vec![Instruction::Rts as u8 | 4]),
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0b0101_0100]),
+        ],
+        4,
+    );
+
+    let frame = CanFrame::new(StandardId::new(1).unwrap(), &[7]).unwrap();
+    assert_eq!(
+        bus.try_transmit_tracked(&frame).unwrap(),
+        mcp25xx::TxBuffer::TXB2
+    );
+    assert!(matches!(
+        bus.try_transmit_tracked(&frame),
+        Err(nb::Error::WouldBlock)
+    ));
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_start() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANCTRL::ADDRESS,
+                0b11100000,
+                0b01000000,
+            ]),
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b1000_0000]),
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0100_0000]),
+        ],
+        3,
+    );
+
+    bus.start(&Config::default().mode(OperationMode::Loopback))
+        .unwrap();
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_read_all_filters_and_masks() {
+    let header = |id: u16| IdHeader::from(StandardId::new(id).unwrap()).id();
+    let bytes = |ids: &[u16]| -> Vec<u8> {
+        ids.iter()
+            .flat_map(|&id| [(id >> 3) as u8, (id as u8 & 0b111) << 5, 0, 0])
+            .collect()
+    };
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, 0x00]),
+            Transaction::transfer(vec![0; 12], bytes(&[0x100, 0x101, 0x102])),
+            Transaction::write(vec![Instruction::Read as u8, 0x10]),
+            Transaction::transfer(vec![0; 12], bytes(&[0x103, 0x104, 0x105])),
+            Transaction::write(vec![Instruction::Read as u8, 0x20]),
+            Transaction::transfer(vec![0; 8], bytes(&[0x7FF, 0x7F0])),
+        ],
+        3,
+    );
+
+    let filters = bus.read_all_filters().unwrap();
+    for (filter, id) in filters.iter().zip(0x100..) {
+        assert_eq!(filter.id(), header(id));
+    }
+    let masks = bus.read_all_masks().unwrap();
+    assert_eq!(masks[0].id(), header(0x7FF));
+    assert_eq!(masks[1].id(), header(0x7F0));
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_receive_remote_frames() {
+    #[cfg(feature = "fast_instructions")]
+    let read = |header: [u8; 5], data_len: usize| {
+        vec![
+            Transaction::write(vec![Instruction::ReadRxBuffer as u8]),
+            Transaction::transfer(vec![0; 5], header.to_vec()),
+            Transaction::transfer(vec![0; data_len], vec![0; data_len]),
+        ]
+    };
+    #[cfg(not(feature = "fast_instructions"))]
+    let read = |header: [u8; 5], data_len: usize| {
+        vec![
+            Transaction::write(vec![Instruction::Read as u8, 0x61]),
+            Transaction::transfer(vec![0; 5], header.to_vec()),
+            Transaction::transfer(vec![0; data_len], vec![0; data_len]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANINTF::ADDRESS,
+                0b01,
+                0,
+            ]),
+        ]
+    };
+    #[cfg(feature = "fast_instructions")]
+    let cs_toggles = 3;
+    #[cfg(not(feature = "fast_instructions"))]
+    let cs_toggles = 6;
+
+    let expectations = [
+        // standard remote frame, RTR in the SRR bit of SIDL
+        read([0x24, 0x70, 0, 0, 2], 2),
+        // extended remote frame, RTR in the DLC register, SRR always set
+        read([0x91, 0xB8, 0x56, 0x78, 0x40 | 3], 3),
+        // extended data frame
+        read([0x91, 0xB8, 0x56, 0x78, 1], 1),
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, cs_toggles);
+
+    let standard = bus.read_rx_buffer(RxBuffer::RXB0).unwrap();
+    assert_eq!(standard.id(), Id::Standard(StandardId::new(0x123).unwrap()));
+    assert!(standard.is_remote_frame());
+    assert_eq!(standard.dlc(), 2);
+
+    let extended = bus.read_rx_buffer(RxBuffer::RXB0).unwrap();
+    assert_eq!(
+        extended.id(),
+        Id::Extended(ExtendedId::new(0x1234_5678).unwrap())
+    );
+    assert!(extended.is_remote_frame());
+    assert_eq!(extended.dlc(), 3);
+
+    let data = bus.read_rx_buffer(RxBuffer::RXB0).unwrap();
+    assert!(data.is_data_frame());
+    assert_eq!(data.data(), &[0]);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_last_raw_dlc() {
+    #[cfg(feature = "fast_instructions")]
+    let (read_instruction, cs_toggles) = (vec![Instruction::ReadRxBuffer as u8 | 2], 1);
+    #[cfg(not(feature = "fast_instructions"))]
+    let (read_instruction, cs_toggles) = (vec![Instruction::Read as u8, 0x71], 2);
+
+    #[cfg(feature = "fast_instructions")]
+    let clear_flag = vec![];
+    #[cfg(not(feature = "fast_instructions"))]
+    let clear_flag = vec![Transaction::write(vec![
+        Instruction::BitModify as u8,
+        CANINTF::ADDRESS,
+        0b10,
+        0,
+    ])];
+
+    let expectations = [
+        vec![
+            Transaction::write(read_instruction),
+            Transaction::transfer(vec![0; 5], vec![0, 0, 0, 0, 12]),
+            Transaction::transfer(vec![0; 8], vec![1, 2, 3, 4, 5, 6, 7, 8]),
+        ],
+        clear_flag,
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, cs_toggles);
+
+    assert_eq!(bus.last_raw_dlc(), 0);
+    let frame = bus.read_rx_buffer(mcp25xx::RxBuffer::RXB1).unwrap();
+    assert_eq!(frame.dlc(), 8);
+    assert_eq!(frame.data(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(bus.last_raw_dlc(), 12);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_strict_dlc() {
+    #[cfg(feature = "fast_instructions")]
+    let (read_instruction, cs_toggles) = (vec![Instruction::ReadRxBuffer as u8], 2);
+    #[cfg(not(feature = "fast_instructions"))]
+    let (read_instruction, cs_toggles) = (vec![Instruction::Read as u8, 0x61], 3);
+
+    #[cfg(feature = "fast_instructions")]
+    let clear_flag = vec![];
+    #[cfg(not(feature = "fast_instructions"))]
+    let clear_flag = vec![Transaction::write(vec![
+        Instruction::BitModify as u8,
+        CANINTF::ADDRESS,
+        0b01,
+        0,
+    ])];
+
+    let expectations = [
+        vec![
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![0b0000_0001]),
+            Transaction::write(read_instruction),
+            Transaction::transfer(vec![0; 5], vec![0, 0, 0, 0, 9]),
+            Transaction::transfer(vec![0; 8], vec![0; 8]),
+        ],
+        clear_flag,
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, cs_toggles).with_strict_dlc(true);
+
+    assert!(matches!(
+        bus.try_receive_checked(),
+        Err(nb::Error::Other(Error::MalformedFrame))
+    ));
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_load_and_send() {
+    #[cfg(feature = "fast_instructions")]
+    let load_instruction = vec![Instruction::LoadTxBuffer as u8 | 2];
+    #[cfg(not(feature = "fast_instructions"))]
+    let load_instruction = vec![Instruction::Write as u8, 0x41];
+
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(load_instruction),
+            Transaction::write(vec![0, 32, 0, 0, 2, 1, 2]),
+            Transaction::write(vec![Instruction::Rts as u8 | 2]),
+        ],
+        2,
+    );
+
+    let frame = CanFrame::new(StandardId::new(1).unwrap(), &[1, 2]).unwrap();
+    bus.load_and_send(mcp25xx::TxBuffer::TXB1, &frame).unwrap();
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_mode_poll_limit() {
+    let poll = [
+        Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+        Transaction::transfer(vec![0], vec![0b1000_0000]),
+    ];
+    let expectations = [
+        &[Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANCTRL::ADDRESS,
+            0b11100000,
+            0b00000000,
+        ])][..],
+        &poll,
+        &poll,
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, 3);
+    bus.set_mode_poll_limit(2);
+
+    assert_eq!(
+        bus.start(&Config::default().mode(OperationMode::NormalOperation)),
+        Err(Error::ModeChangeTimeout)
+    );
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_loopback_session() {
+    let set_mode = |reqop: u8| {
+        Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANCTRL::ADDRESS,
+            0b11100000,
+            reqop,
+        ])
+    };
+    let read_status = |status: u8| {
+        [
+            Transaction::write(vec![Instruction::ReadStatus as u8]),
+            Transaction::transfer(vec![0], vec![status]),
+        ]
+    };
+    #[cfg(feature = "fast_instructions")]
+    let (load_instruction, read_instruction, clear_flag, cs_toggles) = (
+        vec![Instruction::LoadTxBuffer as u8],
+        vec![Instruction::ReadRxBuffer as u8],
+        vec![],
+        11,
+    );
+    #[cfg(not(feature = "fast_instructions"))]
+    let (load_instruction, read_instruction, clear_flag, cs_toggles) = (
+        vec![Instruction::Write as u8, 0x31],
+        vec![Instruction::Read as u8, 0x61],
+        vec![Transaction::write(vec![
+            Instruction::BitModify as u8,
+            CANINTF::ADDRESS,
+            0b01,
+            0,
+        ])],
+        12,
+    );
+
+    let expectations = [
+        vec![
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0000_0000]),
+            set_mode(0b0100_0000),
+            Transaction::write(vec![Instruction::Read as u8, CANSTAT::ADDRESS]),
+            Transaction::transfer(vec![0], vec![0b0100_0000]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                CANINTF::ADDRESS,
+                0b0000_0011,
+                0,
+            ]),
+        ],
+        read_status(0).to_vec(),
+        vec![
+            Transaction::write(load_instruction),
+            Transaction::write(vec![0, 32, 0, 0, 1, 9]),
+            Transaction::write(vec![Instruction::Rts as u8 | 1]),
+        ],
+        // frame not received yet
+        read_status(0b0000_0100).to_vec(),
+        read_status(0b0000_0001).to_vec(),
+        vec![
+            Transaction::write(read_instruction),
+            Transaction::transfer(vec![0; 5], vec![0, 32, 0, 0, 1]),
+            Transaction::transfer(vec![0], vec![9]),
+        ],
+        clear_flag,
+        vec![set_mode(0b0000_0000)],
+    ]
+    .concat();
+    let mut bus = get_mock_bus(&expectations, cs_toggles);
+
+    let frame = CanFrame::new(StandardId::new(1).unwrap(), &[9]).unwrap();
+    let mut session = bus.loopback_session().unwrap();
+    assert_eq!(session.previous_mode(), OperationMode::NormalOperation);
+    let mut delay = RecordingDelay(vec![]);
+    let echo = session.send_and_recv(&frame, &mut delay, 1000).unwrap();
+    assert_eq!(delay.0, [10]);
+    assert_eq!(echo.id(), frame.id());
+    assert_eq!(echo.data(), frame.data());
+    drop(session);
+    bus.cs.done();
+    bus.spi.done();
+}
+
+#[test]
+fn test_rx_overflow() {
+    let mut bus = get_mock_bus(
+        &[
+            Transaction::write(vec![Instruction::Read as u8, EFLG::ADDRESS]),
+            // RX1OVR and bus warning flags
+            Transaction::transfer(vec![0], vec![0b1000_0001]),
+            Transaction::write(vec![
+                Instruction::BitModify as u8,
+                EFLG::ADDRESS,
+                0b1100_0000,
+                0,
+            ]),
+        ],
+        2,
+    );
+    assert_eq!(bus.check_rx_overflow().unwrap(), (false, true));
+    bus.clear_rx_overflow().unwrap();
+    bus.cs.done();
+    bus.spi.done();
+}